@@ -0,0 +1,376 @@
+use raylib::prelude::*;
+use raylib::core::audio::{RaylibAudio, Sound};
+use std::f32::consts::PI;
+
+use crate::levels::Surface;
+use crate::maze::Maze;
+use crate::physics;
+use crate::player::Player;
+use crate::sprites::Enemy;
+
+// A sound that may be missing: without an audio device (or with `--no-audio`) or when the file
+// can't be loaded every call is a no-op, so the game runs the same but silent.
+pub struct Sfx<'a> {
+    sound: Option<Sound<'a>>,
+}
+
+impl<'a> Sfx<'a> {
+    pub fn silent() -> Self {
+        Sfx { sound: None }
+    }
+
+    // loads `path` with `device`, a warning is printed if it fails
+    pub fn load(device: Option<&'a RaylibAudio>, path: &str) -> Self {
+        let Some(device) = device else { return Self::silent(); };
+        match device.new_sound(path) {
+            Ok(sound) => Sfx { sound: Some(sound) },
+            Err(e) => {
+                eprintln!("No se pudo cargar el sonido {path}: {e}");
+                Self::silent()
+            }
+        }
+    }
+
+    pub fn play(&mut self) {
+        if let Some(s) = &mut self.sound { s.play(); }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(s) = &mut self.sound { s.stop(); }
+    }
+
+    // false for the silent stand-in of a missing file
+    pub fn is_loaded(&self) -> bool {
+        self.sound.is_some()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.sound.as_ref().is_some_and(|s| s.is_playing())
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        if let Some(s) = &mut self.sound { s.set_volume(volume); }
+    }
+
+    pub fn set_pan(&mut self, pan: f32) {
+        if let Some(s) = &mut self.sound { s.set_pan(pan); }
+    }
+
+    pub fn set_pitch(&mut self, pitch: f32) {
+        if let Some(s) = &mut self.sound { s.set_pitch(pitch); }
+    }
+}
+
+// None when audio is disabled or no device could be opened
+pub fn init_device(enabled: bool) -> Option<RaylibAudio> {
+    if !enabled { return None; }
+    match RaylibAudio::init_audio_device() {
+        Ok(device) => Some(device),
+        Err(e) => {
+            eprintln!("No se pudo inicializar el audio, se juega sin sonido: {e}");
+            None
+        }
+    }
+}
+
+// Things the gameplay wants heard. The world pushes them while it ticks and the screen playing
+// the level hands them to `Sounds::play`, so the simulation never touches the audio device.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SoundEvent {
+    Footstep(Surface, f32), // with its loudness, 1 walking
+    Pickup,
+    Hurt,
+    Door,           // the iglo is locked until the key is found
+    Alert(Vector2), // an enemy noticed the player from this position
+    Win,
+    Lose,
+}
+
+// footstep sample of each surface, with the pitch and volume it is played at: ice sounds
+// brighter, packed snow a bit muffled
+const FOOTSTEPS: [(Surface, &str, f32, f32); 3] = [
+    (Surface::Ice, "assets/sounds/steps_ice.wav", 1.15, 0.8),
+    (Surface::Snow, "assets/sounds/steps_snow.wav", 0.85, 0.7),
+    (Surface::Stone, "assets/sounds/steps.wav", 1.0, 1.0),
+];
+
+// alerts are heard up to this far, in world pixels
+const ALERT_DISTANCE: f32 = 900.0;
+
+// Layered music: one loop per state, only the current one is heard and changes cross-fade.
+// Playing a level picks explore/threat/chase from the enemies, winning or losing plays their
+// track once. Stingers are short phrases over the music, which ducks while they sound.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MusicState {
+    Explore,
+    Threat, // a hostile enemy is close but hasn't seen the player
+    Chase,  // an enemy is after the player
+    Victory,
+    Defeat,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Stinger {
+    Key,
+    GameOver,
+}
+
+// state, file, volume, whether it loops
+const MUSIC_LAYERS: [(MusicState, &str, f32, bool); 5] = [
+    (MusicState::Explore, "assets/sounds/scary.mp3", 0.3, true),
+    (MusicState::Threat, "assets/sounds/music_threat.wav", 0.35, true),
+    (MusicState::Chase, "assets/sounds/music_chase.wav", 0.4, true),
+    (MusicState::Victory, "assets/sounds/music_victory.wav", 0.4, false),
+    (MusicState::Defeat, "assets/sounds/music_defeat.wav", 0.4, false),
+];
+
+// seconds to fade a layer fully in or out
+const CROSSFADE: f32 = 1.2;
+// threat starts this close to a hostile enemy and ends a bit further, in blocks
+const THREAT_NEAR: f32 = 5.0;
+const THREAT_FAR: f32 = 6.5;
+// enemies that can end the run
+const HOSTILE: [char; 2] = ['e', 'f'];
+// music volume while a stinger plays, it recovers in about a second
+const STINGER_DUCK: f32 = 0.35;
+
+struct MusicLayer<'a> {
+    state: MusicState,
+    sfx: Sfx<'a>,
+    gain: f32,
+    looping: bool,
+    fade: f32,     // 0..1, cross-fade position
+    started: bool, // a track that doesn't loop plays once per time its state is entered
+}
+
+pub struct Music<'a> {
+    layers: Vec<MusicLayer<'a>>,
+    stingers: Vec<(Stinger, Sfx<'a>)>,
+    state: Option<MusicState>, // None until a level starts
+    duck: f32,
+}
+
+impl<'a> Music<'a> {
+    pub fn load(device: Option<&'a RaylibAudio>) -> Self {
+        let layers = MUSIC_LAYERS.iter().map(|&(state, path, gain, looping)| MusicLayer {
+            state, sfx: Sfx::load(device, path), gain, looping, fade: 0.0, started: false,
+        }).collect();
+        let stingers = vec![
+            (Stinger::Key, Sfx::load(device, "assets/sounds/stinger_key.wav")),
+            (Stinger::GameOver, Sfx::load(device, "assets/sounds/stinger_game_over.wav")),
+        ];
+        Music { layers, stingers, state: None, duck: 1.0 }
+    }
+
+    pub fn state(&self) -> Option<MusicState> {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: MusicState) {
+        if self.state == Some(state) { return; }
+        self.state = Some(state);
+        for layer in self.layers.iter_mut().filter(|l| l.state == state) { layer.started = false; }
+    }
+
+    // explore, threat or chase from the hostile enemies around the player: chase needs an alert
+    // enemy that can see the player, one that only heard it (or is just close) is a threat
+    pub fn follow(&mut self, listener: &Player, enemies: &[Enemy], maze: &Maze, block_size: usize) {
+        let mut nearest = f32::MAX;
+        let mut heard = false;
+        for e in enemies.iter().filter(|e| HOSTILE.contains(&e.id)) {
+            if e.alert && physics::line_of_sight(maze, block_size, e.pos, listener.pos) {
+                self.set_state(MusicState::Chase);
+                return;
+            }
+            heard |= e.alert;
+            let (dx, dy) = (e.pos.x - listener.pos.x, e.pos.y - listener.pos.y);
+            nearest = nearest.min((dx * dx + dy * dy).sqrt());
+        }
+        // a wider radius to leave the threat than to enter it, so it doesn't flicker at the edge
+        let threat = if self.state == Some(MusicState::Threat) { THREAT_FAR } else { THREAT_NEAR };
+        if heard || nearest < threat * block_size as f32 {
+            self.set_state(MusicState::Threat);
+        } else {
+            self.set_state(MusicState::Explore);
+        }
+    }
+
+    pub fn stinger(&mut self, which: Stinger) {
+        if let Some((_, sfx)) = self.stingers.iter_mut().find(|(s, _)| *s == which) {
+            sfx.play();
+            self.duck = STINGER_DUCK;
+        }
+    }
+
+    // layer heard for the current state: one whose file is missing leaves the explore track on
+    fn audible_state(&self) -> Option<MusicState> {
+        let state = self.state?;
+        let loaded = self.layers.iter().any(|l| l.state == state && l.sfx.is_loaded());
+        Some(if loaded { state } else { MusicState::Explore })
+    }
+
+    // once per frame, whatever screen is shown
+    pub fn update(&mut self, dt: f32) {
+        self.duck = (self.duck + dt).min(1.0);
+        let step = dt / CROSSFADE;
+        let audible = self.audible_state();
+        for layer in &mut self.layers {
+            let on = audible == Some(layer.state);
+            layer.fade = if on { (layer.fade + step).min(1.0) } else { (layer.fade - step).max(0.0) };
+            if layer.fade <= 0.0 {
+                if layer.sfx.is_playing() { layer.sfx.stop(); }
+                continue;
+            }
+            if on && !layer.sfx.is_playing() && (layer.looping || !layer.started) {
+                layer.sfx.play();
+                layer.started = true;
+            }
+            layer.sfx.set_volume(layer.fade * layer.gain * self.duck);
+        }
+    }
+}
+
+pub struct Sounds<'a> {
+    pub footsteps: Vec<(Surface, Sfx<'a>, f32)>, // with the volume of a walking step
+    pub pickup: Sfx<'a>,
+    pub hurt: Sfx<'a>,
+    pub door: Sfx<'a>,
+    pub alert: Sfx<'a>,
+    pub win: Sfx<'a>,
+    pub lose: Sfx<'a>,
+    pub taylor: Sfx<'a>, // the puffle's song
+    pub chaser: Sfx<'a>, // footsteps of the enemies, positional
+    pub music: Music<'a>,
+}
+
+impl<'a> Sounds<'a> {
+    pub fn load(device: Option<&'a RaylibAudio>) -> Self {
+        let footsteps = FOOTSTEPS.iter().map(|&(surface, path, pitch, volume)| {
+            let mut sfx = Sfx::load(device, path);
+            sfx.set_pitch(pitch);
+            (surface, sfx, volume)
+        }).collect();
+        Sounds {
+            footsteps,
+            pickup: Sfx::load(device, "assets/sounds/pickup.wav"),
+            hurt: Sfx::load(device, "assets/sounds/hurt.wav"),
+            door: Sfx::load(device, "assets/sounds/door.wav"),
+            alert: Sfx::load(device, "assets/sounds/alert.wav"),
+            win: Sfx::load(device, "assets/sounds/win.wav"),
+            lose: Sfx::load(device, "assets/sounds/lose.wav"),
+            taylor: Sfx::load(device, "assets/sounds/tay.wav"),
+            // a second copy of the steps so the enemies can be heard while the player walks
+            chaser: Sfx::load(device, "assets/sounds/steps.wav"),
+            music: Music::load(device),
+        }
+    }
+
+    pub fn play(&mut self, event: SoundEvent, listener: &Player) {
+        match event {
+            SoundEvent::Footstep(surface, loudness) => {
+                if let Some((_, sfx, volume)) = self.footsteps.iter_mut().find(|(s, ..)| *s == surface) {
+                    sfx.set_volume((*volume * loudness).min(1.0));
+                    sfx.play();
+                }
+            }
+            SoundEvent::Pickup => {
+                self.pickup.play();
+                self.music.stinger(Stinger::Key);
+            }
+            SoundEvent::Hurt => self.hurt.play(),
+            SoundEvent::Door => self.door.play(),
+            SoundEvent::Alert(pos) => {
+                let heard = spatialize(listener, pos, ALERT_DISTANCE);
+                // still audible far away, it is a warning
+                self.alert.set_volume(0.3 + 0.7 * heard.volume);
+                self.alert.set_pan(raylib_pan(heard.pan));
+                self.alert.play();
+            }
+            SoundEvent::Win => {
+                self.win.play();
+                self.music.set_state(MusicState::Victory);
+            }
+            SoundEvent::Lose => {
+                self.lose.play();
+                self.music.set_state(MusicState::Defeat);
+                self.music.stinger(Stinger::GameOver);
+            }
+        }
+    }
+}
+
+// Positional audio: sounds attached to kinds of entities get their volume from the distance to
+// the player and their stereo pan from the angle relative to where the player looks, so enemies
+// can be located by ear. Values are smoothed so they don't jump as entities move.
+
+// volume and pan of a source heard from the player; pan is -1 (left) .. 1 (right)
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Spatial {
+    pub volume: f32,
+    pub pan: f32,
+}
+
+// `max_dist` in world pixels; sources behind the player sound a bit quieter
+pub fn spatialize(listener: &Player, source: Vector2, max_dist: f32) -> Spatial {
+    let dx = source.x - listener.pos.x;
+    let dy = source.y - listener.pos.y;
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist >= max_dist { return Spatial::default(); }
+    let falloff = 1.0 - dist / max_dist;
+    let mut rel = dy.atan2(dx) - listener.a;
+    while rel > PI { rel -= 2.0 * PI; }
+    while rel < -PI { rel += 2.0 * PI; }
+    // y grows downwards, so a positive angle is to the right of the heading
+    let behind = if rel.cos() < 0.0 { 0.7 } else { 1.0 };
+    Spatial { volume: falloff * falloff * behind, pan: rel.sin() }
+}
+
+// raylib pans from 1.0 (left) to 0.0 (right), 0.5 is centered
+fn raylib_pan(pan: f32) -> f32 {
+    (0.5 - pan * 0.5).clamp(0.0, 1.0)
+}
+
+// a looping sound played by the loudest entity whose id is in `ids`
+pub struct Emitter {
+    pub ids: &'static [char],
+    pub gain: f32,     // volume at zero distance
+    pub max_dist: f32, // silent from here on
+    current: Spatial,  // smoothed values sent to the sound
+}
+
+impl Emitter {
+    pub fn new(ids: &'static [char], gain: f32, max_dist: f32) -> Self {
+        Emitter { ids, gain, max_dist, current: Spatial::default() }
+    }
+
+    // loudest entity of this emitter's kind
+    fn target(&self, listener: &Player, enemies: &[Enemy]) -> Spatial {
+        enemies.iter()
+            .filter(|e| self.ids.contains(&e.id))
+            .map(|e| spatialize(listener, e.pos, self.max_dist))
+            .fold(Spatial::default(), |best, s| if s.volume > best.volume { s } else { best })
+    }
+
+    // moves towards the target and updates the sound, starting or stopping it as needed
+    pub fn update(&mut self, sound: &mut Sfx, listener: &Player, enemies: &[Enemy], dt: f32) {
+        let target = self.target(listener, enemies);
+        // exponential smoothing, about 90% of the way in 0.25 s
+        let k = 1.0 - (-dt * 9.0).exp();
+        self.current.volume += (target.volume - self.current.volume) * k;
+        self.current.pan += (target.pan - self.current.pan) * k;
+
+        let volume = self.current.volume * self.gain;
+        if volume < 0.01 {
+            if sound.is_playing() { sound.stop(); }
+            return;
+        }
+        if !sound.is_playing() { sound.play(); }
+        sound.set_volume(volume);
+        sound.set_pan(raylib_pan(self.current.pan));
+    }
+
+    pub fn silence(&mut self, sound: &mut Sfx) {
+        self.current = Spatial::default();
+        if sound.is_playing() { sound.stop(); }
+    }
+}
@@ -0,0 +1,153 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::framebuffer::Framebuffer;
+use crate::maze::Maze;
+use crate::minimap::cell_to_color;
+use crate::player::Player;
+use crate::sprites::Enemy;
+
+// Full-screen automap with fog-of-war. Cells are revealed by the rays cast in render_world, so
+// the map only shows what the player has actually seen.
+
+pub type Explored = Vec<Vec<bool>>;
+
+pub fn new_explored(maze: &Maze) -> Explored {
+    maze.iter().map(|row| vec![false; row.len()]).collect()
+}
+
+// Marks the cells crossed by a ray from (x, y) (in blocks) with length `len` blocks, plus the
+// wall cell it hits
+pub fn reveal_ray(explored: &mut Explored, x: f32, y: f32, angle: f32, len: f32) {
+    let (dx, dy) = (angle.cos(), angle.sin());
+    let mut t = 0.0;
+    while t <= len + 0.05 {
+        let (cx, cy) = (x + dx * t, y + dy * t);
+        if cx >= 0.0 && cy >= 0.0 {
+            if let Some(cell) = explored.get_mut(cy as usize).and_then(|row| row.get_mut(cx as usize)) {
+                *cell = true;
+            }
+        }
+        t += 0.25;
+    }
+}
+
+pub struct AutomapView {
+    pub open: bool,
+    pub zoom: f32,    // framebuffer pixels per block
+    pub pan: Vector2, // offset from the player, in blocks
+    pub rotate: bool, // keep the player heading pointing up
+}
+
+impl AutomapView {
+    pub fn new() -> Self {
+        AutomapView { open: false, zoom: 24.0, pan: Vector2::new(0.0, 0.0), rotate: false }
+    }
+
+    // TAB opens/closes; while open: wheel or +/- zoom, IJKL pan, C recenters, R toggles rotation
+    pub fn handle_input(&mut self, rl: &RaylibHandle, dt: f32) {
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            self.open = !self.open;
+        }
+        if !self.open { return; }
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 { self.zoom *= 1.0 + 0.1 * wheel; }
+        if rl.is_key_down(KeyboardKey::KEY_EQUAL) || rl.is_key_down(KeyboardKey::KEY_KP_ADD) { self.zoom *= 1.0 + dt; }
+        if rl.is_key_down(KeyboardKey::KEY_MINUS) || rl.is_key_down(KeyboardKey::KEY_KP_SUBTRACT) { self.zoom /= 1.0 + dt; }
+        self.zoom = self.zoom.clamp(4.0, 96.0);
+
+        let pan_speed = 8.0 * dt;
+        if rl.is_key_down(KeyboardKey::KEY_J) { self.pan.x -= pan_speed; }
+        if rl.is_key_down(KeyboardKey::KEY_L) { self.pan.x += pan_speed; }
+        if rl.is_key_down(KeyboardKey::KEY_I) { self.pan.y -= pan_speed; }
+        if rl.is_key_down(KeyboardKey::KEY_K) { self.pan.y += pan_speed; }
+        if rl.is_key_pressed(KeyboardKey::KEY_C) { self.pan = Vector2::new(0.0, 0.0); }
+        if rl.is_key_pressed(KeyboardKey::KEY_R) { self.rotate = !self.rotate; }
+    }
+
+    pub fn draw(
+        &self,
+        fb: &mut Framebuffer,
+        maze: &Maze,
+        explored: &Explored,
+        player: &Player,
+        enemies: &[Enemy],
+        block_size: usize,
+    ) {
+        let bs = block_size as f32;
+        // rotation that takes the player heading to "up" on screen
+        let rot = if self.rotate { -PI * 0.5 - player.a } else { 0.0 };
+        let (sin_r, cos_r) = rot.sin_cos();
+        let center = Vector2::new(player.pos.x / bs + self.pan.x, player.pos.y / bs + self.pan.y);
+        let (half_w, half_h) = (fb.width as f32 * 0.5, fb.height as f32 * 0.5);
+        let background = Color::new(10, 10, 20, 255);
+        let floor = Color::new(45, 45, 60, 255);
+
+        // every pixel is taken back to map coordinates so rotation doesn't leave holes
+        for sy in 0..fb.height {
+            for sx in 0..fb.width {
+                let (ox, oy) = ((sx as f32 - half_w) / self.zoom, (sy as f32 - half_h) / self.zoom);
+                let mx = center.x + ox * cos_r + oy * sin_r;
+                let my = center.y - ox * sin_r + oy * cos_r;
+                let mut col = background;
+                if mx >= 0.0 && my >= 0.0 {
+                    let (cx, cy) = (mx as usize, my as usize);
+                    let seen = explored.get(cy).and_then(|r| r.get(cx)).copied().unwrap_or(false);
+                    if seen {
+                        let cell = maze[cy][cx];
+                        col = if cell == ' ' { floor } else { cell_to_color(cell) };
+                    }
+                }
+                fb.set_pixel_color(sx, sy, col);
+            }
+        }
+
+        // map (blocks) -> framebuffer pixels
+        let to_screen = |mx: f32, my: f32| -> (i32, i32) {
+            let (dx, dy) = (mx - center.x, my - center.y);
+            let ox = dx * cos_r - dy * sin_r;
+            let oy = dx * sin_r + dy * cos_r;
+            ((half_w + ox * self.zoom) as i32, (half_h + oy * self.zoom) as i32)
+        };
+        let marker = |fb: &mut Framebuffer, x: i32, y: i32, r: i32, c: Color| {
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    if x + dx >= 0 && y + dy >= 0 { fb.set_pixel_color((x + dx) as u32, (y + dy) as u32, c); }
+                }
+            }
+        };
+        let r = (self.zoom * 0.2).max(2.0) as i32;
+
+        // goal markers on the discovered 'g' cells
+        for (cy, row) in maze.iter().enumerate() {
+            for (cx, &cell) in row.iter().enumerate() {
+                if cell == 'g' && explored[cy][cx] {
+                    let (x, y) = to_screen(cx as f32 + 0.5, cy as f32 + 0.5);
+                    marker(fb, x, y, r + 2, Color::LIME);
+                }
+            }
+        }
+
+        // items that were seen (the key and the puffle); hostile enemies move so they aren't mapped
+        for e in enemies {
+            let color = match e.id { 'k' => Color::GOLD, 'p' => Color::PINK, _ => continue };
+            let (mx, my) = (e.pos.x / bs, e.pos.y / bs);
+            if mx < 0.0 || my < 0.0 { continue; }
+            let seen = explored.get(my as usize).and_then(|r| r.get(mx as usize)).copied().unwrap_or(false);
+            if !seen { continue; }
+            let (x, y) = to_screen(mx, my);
+            marker(fb, x, y, r, color);
+        }
+
+        // player and heading
+        let (px, py) = to_screen(player.pos.x / bs, player.pos.y / bs);
+        marker(fb, px, py, r, Color::VIOLET);
+        let (hx, hy) = to_screen(player.pos.x / bs + player.a.cos(), player.pos.y / bs + player.a.sin());
+        let steps = ((hx - px).abs().max((hy - py).abs())).max(1);
+        for i in 0..=steps {
+            let x = px + (hx - px) * i / steps;
+            let y = py + (hy - py) * i / steps;
+            if x >= 0 && y >= 0 { fb.set_pixel_color(x as u32, y as u32, Color::YELLOW); }
+        }
+    }
+}
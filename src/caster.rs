@@ -5,6 +5,7 @@ use crate::player::Player;
 
 //class that throws rays that impact the maze walls and returns the intersection data
 
+#[derive(Copy, Clone)]
 pub struct Intersect {
   pub distance: f32,    //distance to thw walls
   pub impact: char,     //symbol of the wall hit
@@ -24,7 +25,16 @@ pub fn cast_ray(
   draw_line: bool,
 ) -> Intersect {
   if draw_line { return cast_ray_debug(framebuffer, maze, player, a, block_size); }
+  cast_ray_grid(maze, player, a, block_size)
+}
 
+//DDA ray cast that doesn't touch the framebuffer, so it can run on worker threads
+pub fn cast_ray_grid(
+  maze: &Maze,
+  player: &Player,
+  a: f32,
+  block_size: usize,
+) -> Intersect {
   let maze_h = maze.len();
   if maze_h == 0 { return empty(); }
   let maze_w = maze[0].len();
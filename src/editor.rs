@@ -0,0 +1,253 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::io;
+
+use crate::framebuffer::Framebuffer;
+use crate::input::PlayerInput;
+use crate::levels::{LEVELS, load_entities, save_entities};
+use crate::maze::{Maze, load_maze};
+use crate::minimap::draw_cell;
+use crate::player::{Player, process_events};
+use crate::sprites::{Enemy, TRANSPARENT_COLOR};
+use crate::textures::TextureManager;
+
+// Top-down level editor: paints wall glyphs, places entities and the player start, previews the
+// level in first person and saves the maze file plus an entities file next to it.
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Brush {
+    Glyph(char),  // wall glyph, ' ' erases
+    Entity(char), // enemy or item id
+    PlayerStart,
+}
+
+// Brushes selected with the number keys
+pub const BRUSHES: [(KeyboardKey, Brush, &str); 10] = [
+    (KeyboardKey::KEY_ONE, Brush::Glyph('+'), "esquina +"),
+    (KeyboardKey::KEY_TWO, Brush::Glyph('-'), "muro -"),
+    (KeyboardKey::KEY_THREE, Brush::Glyph('|'), "muro |"),
+    (KeyboardKey::KEY_FOUR, Brush::Glyph('g'), "meta g"),
+    (KeyboardKey::KEY_FIVE, Brush::Glyph(' '), "borrar"),
+    (KeyboardKey::KEY_SIX, Brush::Entity('e'), "enemigo e"),
+    (KeyboardKey::KEY_SEVEN, Brush::Entity('f'), "perseguidor f"),
+    (KeyboardKey::KEY_EIGHT, Brush::Entity('k'), "llave k"),
+    (KeyboardKey::KEY_NINE, Brush::Entity('p'), "puffle p"),
+    (KeyboardKey::KEY_ZERO, Brush::PlayerStart, "inicio del jugador"),
+];
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum EditorAction {
+    None,
+    Exit,
+}
+
+pub struct Editor {
+    pub level: usize,
+    pub maze: Maze,
+    pub player_start: (f32, f32, f32), // blocks, like LevelDef
+    pub entities: Vec<(f32, f32, char)>,
+    pub brush: usize,                  // index in BRUSHES
+    pub preview: Option<(Player, Vec<Enemy>)>,
+    pub status: String,
+    cursor: Option<(f32, f32)>, // mouse position in blocks
+    block_size: usize,
+}
+
+impl Editor {
+    pub fn open(level: usize, block_size: usize) -> Self {
+        let def = &LEVELS[level];
+        let mut maze = load_maze(def.maze_path);
+        // every row with the same width so any block can be painted
+        let width = maze.iter().map(|r| r.len()).max().unwrap_or(0);
+        for row in &mut maze { row.resize(width, ' '); }
+        let (player_start, entities) = load_entities(def.maze_path)
+            .unwrap_or_else(|| (def.player_start, def.enemies.to_vec()));
+        Editor {
+            level,
+            maze,
+            player_start,
+            entities,
+            brush: 0,
+            preview: None,
+            status: format!("Editando {}", def.name),
+            cursor: None,
+            block_size,
+        }
+    }
+
+    // pixels per maze block so the whole level fits in the framebuffer
+    fn cell_px(&self, fb: &Framebuffer) -> u32 {
+        let rows = self.maze.len().max(1) as u32;
+        let cols = self.maze.first().map(|r| r.len()).unwrap_or(1).max(1) as u32;
+        (fb.width / cols).min(fb.height / rows).max(1)
+    }
+
+    pub fn brush_name(&self) -> &'static str {
+        BRUSHES[self.brush].2
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle, fb: &Framebuffer) -> EditorAction {
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            if self.preview.is_some() {
+                self.preview = None;
+                rl.enable_cursor();
+            } else {
+                self.start_preview();
+                rl.disable_cursor();
+            }
+        }
+
+        // first person preview: walk around without enemies moving
+        if let Some((player, _)) = self.preview.as_mut() {
+            let mut input = PlayerInput::read_keys(rl);
+            input.mouse_dx = rl.get_mouse_delta().x;
+            process_events(player, &input, rl.get_frame_time(), &self.maze, self.block_size);
+            return EditorAction::None;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            return EditorAction::Exit;
+        }
+        for (i, (key, _, _)) in BRUSHES.iter().enumerate() {
+            if rl.is_key_pressed(*key) { self.brush = i; }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_R) {
+            self.player_start.2 = (self.player_start.2 + PI * 0.5).rem_euclid(2.0 * PI);
+        }
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_S) {
+            self.status = match self.save() {
+                Ok(()) => format!("Guardado {}{}", LEVELS[self.level].maze_path, self.warnings()),
+                Err(e) => format!("No se pudo guardar: {e}"),
+            };
+        }
+
+        // mouse in window pixels -> framebuffer pixels -> blocks
+        let m = rl.get_mouse_position();
+        let cell = self.cell_px(fb) as f32;
+        let fx = m.x * fb.width as f32 / rl.get_screen_width().max(1) as f32;
+        let fy = m.y * fb.height as f32 / rl.get_screen_height().max(1) as f32;
+        let (bx, by) = (fx / cell, fy / cell);
+        let inside = by >= 0.0 && bx >= 0.0 && (by as usize) < self.maze.len()
+            && self.maze.first().map(|r| (bx as usize) < r.len()).unwrap_or(false);
+        self.cursor = if inside { Some((bx, by)) } else { None };
+        if !inside { return EditorAction::None; }
+
+        // entities snap to half blocks
+        let snap = |v: f32| (v * 2.0).round() * 0.5;
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            match BRUSHES[self.brush].1 {
+                Brush::Glyph(c) => self.maze[by as usize][bx as usize] = c,
+                Brush::Entity(id) => {
+                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                        self.entities.push((snap(bx), snap(by), id));
+                    }
+                }
+                Brush::PlayerStart => self.player_start = (snap(bx), snap(by), self.player_start.2),
+            }
+        }
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT) {
+            let before = self.entities.len();
+            self.entities.retain(|(x, y, _)| (x - bx).abs() > 0.5 || (y - by).abs() > 0.5);
+            if self.entities.len() == before {
+                self.maze[by as usize][bx as usize] = ' ';
+            }
+        }
+        EditorAction::None
+    }
+
+    fn start_preview(&mut self) {
+        let bs = self.block_size as f32;
+        let player = Player::new(Vector2::new(self.player_start.0 * bs, self.player_start.1 * bs), self.player_start.2);
+        let enemies = self.entities.iter().map(|(x, y, id)| Enemy::new(x * bs, y * bs, *id)).collect();
+        self.preview = Some((player, enemies));
+    }
+
+    // things that make the level unplayable, appended to the save message
+    fn warnings(&self) -> String {
+        let mut w = String::new();
+        if !self.maze.iter().any(|r| r.contains(&'g')) { w.push_str(" (falta la meta 'g')"); }
+        if !self.entities.iter().any(|e| e.2 == 'k') { w.push_str(" (falta la llave 'k')"); }
+        w
+    }
+
+    // Writes the maze in the usual glyph format and the entities file next to it
+    pub fn save(&self) -> io::Result<()> {
+        let path = LEVELS[self.level].maze_path;
+        let mut text = String::new();
+        for row in &self.maze {
+            text.extend(row.iter());
+            text.push('\n');
+        }
+        std::fs::write(path, text)?;
+        save_entities(path, self.player_start, &self.entities)
+    }
+
+    // Top-down view of the level
+    pub fn draw(&self, fb: &mut Framebuffer, tex: &TextureManager) {
+        let cell = self.cell_px(fb);
+        for (y, row) in self.maze.iter().enumerate() {
+            for (x, &c) in row.iter().enumerate() {
+                draw_cell(fb, x * cell as usize, y * cell as usize, cell as usize, c);
+            }
+        }
+        for &(x, y, id) in &self.entities {
+            draw_icon(fb, tex, x * cell as f32, y * cell as f32, cell, id);
+        }
+
+        // player start and heading
+        let (sx, sy, sa) = self.player_start;
+        let (px, py) = (sx * cell as f32, sy * cell as f32);
+        let r = (cell / 4).max(2) as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                fb.set_pixel_color((px as i32 + dx).max(0) as u32, (py as i32 + dy).max(0) as u32, Color::VIOLET);
+            }
+        }
+        for i in 0..cell as i32 {
+            let x = px + sa.cos() * i as f32;
+            let y = py + sa.sin() * i as f32;
+            if x >= 0.0 && y >= 0.0 { fb.set_pixel_color(x as u32, y as u32, Color::YELLOW); }
+        }
+
+        // outline of the block under the mouse
+        if let Some((bx, by)) = self.cursor {
+            let (x0, y0) = (bx as u32 * cell, by as u32 * cell);
+            for i in 0..cell {
+                fb.set_pixel_color(x0 + i, y0, Color::ORANGE);
+                fb.set_pixel_color(x0 + i, y0 + cell - 1, Color::ORANGE);
+                fb.set_pixel_color(x0, y0 + i, Color::ORANGE);
+                fb.set_pixel_color(x0 + cell - 1, y0 + i, Color::ORANGE);
+            }
+        }
+    }
+
+    pub fn help_lines(&self) -> Vec<String> {
+        if self.preview.is_some() {
+            return vec!["Vista previa: WASD + mouse, Shift correr, Ctrl agacharse, TAB para volver".to_string()];
+        }
+        vec![
+            format!("Pincel: {}  (1-0 cambia)", self.brush_name()),
+            "Click izq: pintar/colocar  Click der: borrar  R: girar inicio".to_string(),
+            "TAB: vista previa  Ctrl+S: guardar  M: menú".to_string(),
+            self.status.clone(),
+        ]
+    }
+}
+
+// sprite of an entity centered on (cx, cy), one block wide
+fn draw_icon(fb: &mut Framebuffer, tex: &TextureManager, cx: f32, cy: f32, size: u32, id: char) {
+    let (tw, th) = tex.get_size(id);
+    let x0 = cx as i32 - size as i32 / 2;
+    let y0 = cy as i32 - size as i32 / 2;
+    for dy in 0..size {
+        let ty = dy * th / size;
+        for dx in 0..size {
+            let tx = dx * tw / size;
+            let c = tex.sample(id, tx, ty);
+            if c == TRANSPARENT_COLOR || c.a == 0 || (c.r, c.g, c.b) == (255, 0, 255) { continue; }
+            let (x, y) = (x0 + dx as i32, y0 + dy as i32);
+            if x >= 0 && y >= 0 { fb.blend_pixel_color(x as u32, y as u32, c); }
+        }
+    }
+}
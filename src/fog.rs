@@ -0,0 +1,101 @@
+use raylib::prelude::*;
+
+// Unified fog model used by walls, floor, sky and sprites.
+// Distances are in world units (pixels, one cell = block_size).
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FogMode {
+    Linear,
+    Exponential,
+}
+
+#[derive(Copy, Clone)]
+pub struct Fog {
+    pub mode: FogMode,
+    pub color: Color,
+    pub start: f32,   // distance where fog begins
+    pub end: f32,     // linear: distance where fog is total
+    pub density: f32, // exponential: how fast fog thickens after start
+    pub sky: f32,     // how much fog covers the sky at the horizon (0..1)
+}
+
+impl Fog {
+    pub const fn linear(color: Color, start: f32, end: f32, sky: f32) -> Self {
+        Fog { mode: FogMode::Linear, color, start, end, density: 0.0, sky }
+    }
+
+    pub const fn exponential(color: Color, start: f32, density: f32, sky: f32) -> Self {
+        Fog { mode: FogMode::Exponential, color, start, end: f32::INFINITY, density, sky }
+    }
+
+    // 0.0 = no fog, 1.0 = only fog color
+    pub fn factor(&self, dist: f32) -> f32 {
+        if dist <= self.start { return 0.0; }
+        let d = dist - self.start;
+        match self.mode {
+            FogMode::Linear => {
+                let range = (self.end - self.start).max(1.0);
+                (d / range).clamp(0.0, 1.0)
+            }
+            FogMode::Exponential => (1.0 - (-d * self.density).exp()).clamp(0.0, 1.0),
+        }
+    }
+
+    // fog amount for a sky row, v goes from 0 (top) to 1 (horizon)
+    pub fn sky_factor(&self, v: f32) -> f32 {
+        let t = v.clamp(0.0, 1.0);
+        self.sky * t * t
+    }
+
+    pub fn apply(&self, c: Color, dist: f32) -> Color {
+        self.mix(c, self.factor(dist))
+    }
+
+    pub fn mix(&self, c: Color, f: f32) -> Color {
+        if f <= 0.0 { return c; }
+        let k = 1.0 - f;
+        Color::new(
+            (c.r as f32 * k + self.color.r as f32 * f) as u8,
+            (c.g as f32 * k + self.color.g as f32 * f) as u8,
+            (c.b as f32 * k + self.color.b as f32 * f) as u8,
+            c.a,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_fog_before_start() {
+        let fog = Fog::linear(Color::BLACK, 100.0, 300.0, 0.0);
+        assert_eq!(fog.factor(0.0), 0.0);
+        assert_eq!(fog.factor(100.0), 0.0);
+        let fog = Fog::exponential(Color::BLACK, 50.0, 0.01, 0.0);
+        assert_eq!(fog.factor(50.0), 0.0);
+    }
+
+    #[test]
+    fn linear_ramps_to_full_at_end() {
+        let fog = Fog::linear(Color::BLACK, 100.0, 300.0, 0.0);
+        assert!((fog.factor(200.0) - 0.5).abs() < 1e-6);
+        assert_eq!(fog.factor(300.0), 1.0);
+        assert_eq!(fog.factor(10_000.0), 1.0);
+    }
+
+    #[test]
+    fn exponential_grows_and_stays_below_one() {
+        let fog = Fog::exponential(Color::BLACK, 0.0, 0.01, 0.0);
+        let (near, far) = (fog.factor(50.0), fog.factor(200.0));
+        assert!(near > 0.0 && near < far && far < 1.0);
+        assert!((near - (1.0 - (-0.5f32).exp())).abs() < 1e-6);
+    }
+
+    #[test]
+    fn degenerate_range_does_not_divide_by_zero() {
+        let fog = Fog::linear(Color::BLACK, 100.0, 100.0, 0.0);
+        assert!(fog.factor(100.5).is_finite());
+        assert_eq!(fog.factor(101.0), 1.0);
+    }
+}
@@ -0,0 +1,254 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+
+// Bitmap font drawn straight into the Framebuffer, so text is part of the image (exports,
+// shading, in-world signs) instead of being drawn by raylib on top of the window.
+// Glyphs are 5x7 pixels; two extra rows above hold the accents of capital letters, which gives a
+// 6x10 cell with the spacing. Accented letters are a base glyph plus a mark.
+
+const GLYPH_W: i32 = 5;
+const GLYPH_H: i32 = 7;
+const MARK_ROWS: i32 = 2;
+pub const CELL_W: i32 = GLYPH_W + 1;
+pub const CELL_H: i32 = MARK_ROWS + GLYPH_H + 1;
+
+// ASCII 32..=126, one byte per row, bit 4 is the leftmost pixel
+const ASCII: [[u8; 7]; 95] = [
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // ' '
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100], // !
+    [0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000], // "
+    [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010], // #
+    [0b00100, 0b01111, 0b10100, 0b01110, 0b00101, 0b11110, 0b00100], // $
+    [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011], // %
+    [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101], // &
+    [0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000], // '
+    [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010], // (
+    [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000], // )
+    [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000], // *
+    [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000], // +
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000], // ,
+    [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000], // -
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100], // .
+    [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000], // /
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110], // 0
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // 1
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111], // 2
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110], // 3
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010], // 4
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110], // 5
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110], // 6
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000], // 7
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110], // 8
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100], // 9
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000], // :
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000], // ;
+    [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010], // <
+    [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000], // =
+    [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000], // >
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100], // ?
+    [0b01110, 0b10001, 0b00001, 0b01101, 0b10101, 0b10101, 0b01110], // @
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // A
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110], // B
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110], // C
+    [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100], // D
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111], // E
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000], // F
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111], // G
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // H
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // I
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100], // J
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001], // K
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111], // L
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001], // M
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001], // N
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // O
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000], // P
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101], // Q
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001], // R
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110], // S
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // T
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // U
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // V
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010], // W
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001], // X
+    [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100], // Y
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111], // Z
+    [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110], // [
+    [0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000], // \
+    [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110], // ]
+    [0b00100, 0b01010, 0b10001, 0b00000, 0b00000, 0b00000, 0b00000], // ^
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111], // _
+    [0b01000, 0b00100, 0b00010, 0b00000, 0b00000, 0b00000, 0b00000], // `
+    [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111], // a
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110], // b
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110], // c
+    [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111], // d
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110], // e
+    [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000], // f
+    [0b00000, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110], // g
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001], // h
+    [0b00100, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110], // i
+    [0b00010, 0b00000, 0b00110, 0b00010, 0b00010, 0b10010, 0b01100], // j
+    [0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010], // k
+    [0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // l
+    [0b00000, 0b00000, 0b11010, 0b10101, 0b10101, 0b10001, 0b10001], // m
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001], // n
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110], // o
+    [0b00000, 0b00000, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000], // p
+    [0b00000, 0b00000, 0b01101, 0b10011, 0b01111, 0b00001, 0b00001], // q
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000], // r
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110], // s
+    [0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110], // t
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101], // u
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // v
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010], // w
+    [0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001], // x
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110], // y
+    [0b00000, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111], // z
+    [0b00010, 0b00100, 0b00100, 0b01000, 0b00100, 0b00100, 0b00010], // {
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // |
+    [0b01000, 0b00100, 0b00100, 0b00010, 0b00100, 0b00100, 0b01000], // }
+    [0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000], // ~
+];
+
+const DOTLESS_I: [u8; 7] = [0b00000, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110];
+const INV_EXCLAMATION: [u8; 7] = [0b00100, 0b00000, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100];
+const INV_QUESTION: [u8; 7] = [0b00100, 0b00000, 0b00100, 0b01000, 0b10000, 0b10001, 0b01110];
+const UNKNOWN: [u8; 7] = ASCII[(b'?' - 32) as usize];
+
+#[derive(Copy, Clone)]
+enum Mark {
+    Acute,
+    Tilde,
+    Diaeresis,
+}
+
+impl Mark {
+    fn rows(self) -> [u8; 2] {
+        match self {
+            Mark::Acute => [0b00010, 0b00100],
+            Mark::Tilde => [0b01101, 0b10110],
+            Mark::Diaeresis => [0b01010, 0b00000],
+        }
+    }
+}
+
+// base glyph and optional mark for a character; unsupported characters show as '?'
+fn glyph(c: char) -> ([u8; 7], Option<Mark>) {
+    let ascii = |c: char| ASCII[(c as u8 - 32) as usize];
+    match c {
+        ' '..='~' => (ascii(c), None),
+        'á' => (ascii('a'), Some(Mark::Acute)),
+        'é' => (ascii('e'), Some(Mark::Acute)),
+        'í' => (DOTLESS_I, Some(Mark::Acute)),
+        'ó' => (ascii('o'), Some(Mark::Acute)),
+        'ú' => (ascii('u'), Some(Mark::Acute)),
+        'ü' => (ascii('u'), Some(Mark::Diaeresis)),
+        'ñ' => (ascii('n'), Some(Mark::Tilde)),
+        'Á' => (ascii('A'), Some(Mark::Acute)),
+        'É' => (ascii('E'), Some(Mark::Acute)),
+        'Í' => (ascii('I'), Some(Mark::Acute)),
+        'Ó' => (ascii('O'), Some(Mark::Acute)),
+        'Ú' => (ascii('U'), Some(Mark::Acute)),
+        'Ü' => (ascii('U'), Some(Mark::Diaeresis)),
+        'Ñ' => (ascii('N'), Some(Mark::Tilde)),
+        '¡' => (INV_EXCLAMATION, None),
+        '¿' => (INV_QUESTION, None),
+        _ => (UNKNOWN, None),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Copy, Clone)]
+pub struct TextStyle {
+    pub size: f32, // line height in framebuffer pixels
+    pub color: Color,
+    pub align: Align,
+    pub shadow: Option<Color>, // drawn one font pixel down and right
+}
+
+impl TextStyle {
+    pub fn new(size: f32, color: Color) -> Self {
+        TextStyle { size, color, align: Align::Left, shadow: None }
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn shadow(mut self, color: Color) -> Self {
+        self.shadow = Some(color);
+        self
+    }
+}
+
+// width of the widest line and total height, in pixels, for a line height of `size`
+pub fn measure_text(text: &str, size: f32) -> (i32, i32) {
+    let unit = size / CELL_H as f32;
+    let cols = text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as f32;
+    let lines = text.lines().count().max(1) as f32;
+    // the spacing after the last glyph doesn't count
+    let w = (cols * CELL_W as f32 - 1.0).max(0.0) * unit;
+    (w.round() as i32, (lines * size).round() as i32)
+}
+
+// draws `text` with its top at `y`; `x` is the left edge, the center or the right edge of each
+// line depending on the alignment. '\n' starts a new line.
+pub fn draw_text(fb: &mut Framebuffer, text: &str, x: i32, y: i32, style: &TextStyle) {
+    let unit = style.size / CELL_H as f32;
+    if let Some(shadow) = style.shadow {
+        let off = unit.max(1.0).round() as i32;
+        draw_lines(fb, text, x + off, y + off, unit, style.align, shadow);
+    }
+    draw_lines(fb, text, x, y, unit, style.align, style.color);
+}
+
+fn draw_lines(fb: &mut Framebuffer, text: &str, x: i32, y: i32, unit: f32, align: Align, color: Color) {
+    for (li, line) in text.lines().enumerate() {
+        let (w, _) = measure_text(line, unit * CELL_H as f32);
+        let left = match align {
+            Align::Left => x,
+            Align::Center => x - w / 2,
+            Align::Right => x - w,
+        } as f32;
+        let top = y as f32 + li as f32 * CELL_H as f32 * unit;
+        for (ci, c) in line.chars().enumerate() {
+            let gx = left + (ci as i32 * CELL_W) as f32 * unit;
+            let (rows, mark) = glyph(c);
+            for (ry, bits) in rows.iter().enumerate() {
+                draw_row(fb, *bits, gx, top + (MARK_ROWS + ry as i32) as f32 * unit, unit, color);
+            }
+            if let Some(mark) = mark {
+                // lowercase letters leave the first glyph rows free, capitals use the rows above
+                let mark_top = if c.is_lowercase() { MARK_ROWS } else { 0 };
+                for (ry, bits) in mark.rows().iter().enumerate() {
+                    draw_row(fb, *bits, gx, top + (mark_top + ry as i32) as f32 * unit, unit, color);
+                }
+            }
+        }
+    }
+}
+
+// one row of font pixels, each scaled to a unit x unit block
+fn draw_row(fb: &mut Framebuffer, bits: u8, x: f32, y: f32, unit: f32, color: Color) {
+    if bits == 0 { return; }
+    let (y0, y1) = (y.round() as i32, (y + unit).round() as i32);
+    for col in 0..GLYPH_W {
+        if bits & (1 << (GLYPH_W - 1 - col)) == 0 { continue; }
+        let x0 = (x + col as f32 * unit).round() as i32;
+        let x1 = (x + (col + 1) as f32 * unit).round() as i32;
+        for py in y0.max(0)..y1.max(y0 + 1) {
+            for px in x0.max(0)..x1.max(x0 + 1) {
+                fb.blend_pixel_color(px as u32, py as u32, color);
+            }
+        }
+    }
+}
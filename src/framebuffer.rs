@@ -1,11 +1,14 @@
 use raylib::prelude::*;
 
+use crate::font::{draw_text, Align, TextStyle};
+
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
     pub color_buffer: Image,
     background_color: Color,
     current_color: Color,
+    upscale_filter: TextureFilter, // filtro al escalar el buffer al tamaño de la ventana
 }
 
 impl Framebuffer {
@@ -17,24 +20,78 @@ impl Framebuffer {
             color_buffer,
             background_color: Color::BLACK,
             current_color: Color::WHITE,
+            upscale_filter: TextureFilter::TEXTURE_FILTER_POINT,
         }
     }
 
+    // Recrea el buffer con otra resolución interna (cambio de escala o de tamaño de ventana)
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == self.width && height == self.height { return; }
+        self.width = width;
+        self.height = height;
+        self.color_buffer = Image::gen_image_color(width as i32, height as i32, self.background_color);
+    }
+
+    pub fn set_upscale_filter(&mut self, filter: TextureFilter) {
+        self.upscale_filter = filter;
+    }
+
     pub fn clear(&mut self) {
 
         self.color_buffer.clear_background(self.background_color);
     }
 
+    pub fn clear_with(&mut self, color: Color) {
+        self.color_buffer.clear_background(color);
+    }
+
     pub fn set_pixel(&mut self, x: u32, y: u32) {
+        let color = self.current_color;
+        self.set_pixel_color(x, y, color);
+    }
+
+    pub fn set_pixel_color(&mut self, x: u32, y: u32, color: Color) {
         if x < self.width && y < self.height {
-            self.color_buffer.draw_pixel(x as i32, y as i32, self.current_color);
+            let idx = (y * self.width + x) as usize;
+            self.pixels_mut()[idx] = color;
         }
     }
 
-    pub fn set_pixel_color(&mut self, x: u32, y: u32, color: Color) {
+    // Row-major view of the pixels, used by the renderer to split rows across threads
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        // gen_image_color crea el buffer en RGBA8, así que cada pixel es un Color
+        let len = (self.width * self.height) as usize;
+        unsafe { std::slice::from_raw_parts_mut(self.color_buffer.data as *mut Color, len) }
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        let len = (self.width * self.height) as usize;
+        unsafe { std::slice::from_raw_parts(self.color_buffer.data as *const Color, len) }
+    }
+
+    pub fn get_pixel_color(&self, x: u32, y: u32) -> Color {
         if x < self.width && y < self.height {
-            self.color_buffer.draw_pixel(x as i32, y as i32, color);
+            let data = self.color_buffer.data as *const Color;
+            let idx = (y * self.width + x) as usize;
+            return unsafe { *data.add(idx) };
         }
+        self.background_color
+    }
+
+    // Mezcla el color con lo que ya hay en el buffer usando su canal alfa (over)
+    pub fn blend_pixel_color(&mut self, x: u32, y: u32, color: Color) {
+        if color.a == 0 { return; }
+        if color.a == 255 {
+            self.set_pixel_color(x, y, color);
+            return;
+        }
+        if x >= self.width || y >= self.height { return; }
+        let dst = self.get_pixel_color(x, y);
+        let a = color.a as u32;
+        let inv = 255 - a;
+        let mix = |s: u8, d: u8| ((s as u32 * a + d as u32 * inv + 127) / 255) as u8;
+        let out = Color::new(mix(color.r, dst.r), mix(color.g, dst.g), mix(color.b, dst.b), 255);
+        self.set_pixel_color(x, y, out);
     }
 
     pub fn set_background_color(&mut self, color: Color) {
@@ -49,27 +106,33 @@ impl Framebuffer {
         self.color_buffer.export_image(file_path);
     }
 
+    // El FPS se dibuja con la fuente bitmap dentro del buffer antes de subirlo
     pub fn swap_buffers(
-        &self,
+        &mut self,
         window: &mut RaylibHandle,
         raylib_thread: &RaylibThread,
         fps: bool,
     ) {
+        if fps {
+            let fps_text = format!("FPS: {}", window.get_fps());
+            let size = (self.height as f32 / 36.0).max(10.0);
+            let style = TextStyle::new(size, Color::WHITE).align(Align::Right).shadow(Color::BLACK);
+            draw_text(self, &fps_text, self.width as i32 - 10, 10, &style);
+        }
         if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
+            texture.set_texture_filter(raylib_thread, self.upscale_filter);
+            let screen_w = window.get_screen_width();
+            let screen_h = window.get_screen_height();
             let mut renderer = window.begin_drawing(raylib_thread);
-            renderer.draw_texture(&texture, 0, 0, Color::WHITE);
-        
-
-            if fps {
-                let num_fps = renderer.get_fps();
-                let fps_text = format!("FPS: {num_fps}");
-                let font_size = 20;
-                let text_width = renderer.measure_text(&fps_text, font_size);
-
-                let x = self.width as i32 - text_width - 10;
-                let y = 10;
-                renderer.draw_text(&fps_text, x, y, font_size, Color::WHITE);
-            }
+            // El buffer puede tener una resolución interna menor: se escala a toda la ventana
+            renderer.draw_texture_pro(
+                &texture,
+                Rectangle::new(0.0, 0.0, self.width as f32, self.height as f32),
+                Rectangle::new(0.0, 0.0, screen_w as f32, screen_h as f32),
+                Vector2::new(0.0, 0.0),
+                0.0,
+                Color::WHITE,
+            );
         }
     }
-}
\ No newline at end of file
+}
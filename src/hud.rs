@@ -0,0 +1,159 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::textures::TextureManager;
+use crate::font::{draw_text, measure_text, TextStyle};
+
+// HUD widgets anchored to the corners/edges of the framebuffer. Sizes and offsets are given in
+// reference pixels (a 720 px tall screen) and scaled to the internal resolution, so the layout
+// keeps its proportions when the window or the render scale changes.
+
+pub const REFERENCE_HEIGHT: f32 = 720.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    // (0, 0.5 or 1) fraction of the free space on each axis
+    fn fractions(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+// area resolved in framebuffer pixels
+#[derive(Copy, Clone, Debug)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+pub struct Hud {
+    width: i32,
+    height: i32,
+    pub scale: f32, // framebuffer pixels per reference pixel
+}
+
+impl Hud {
+    pub fn new(framebuffer: &Framebuffer) -> Self {
+        Hud {
+            width: framebuffer.width as i32,
+            height: framebuffer.height as i32,
+            scale: framebuffer.height as f32 / REFERENCE_HEIGHT,
+        }
+    }
+
+    // reference pixels -> framebuffer pixels, never less than one
+    pub fn px(&self, v: f32) -> i32 {
+        ((v * self.scale).round() as i32).max(1)
+    }
+
+    // `offset` moves the widget away from its anchor (inwards), `size` is in framebuffer pixels
+    pub fn place_px(&self, anchor: Anchor, offset: (f32, f32), size: (i32, i32)) -> Rect {
+        let (fx, fy) = anchor.fractions();
+        let (ox, oy) = (offset.0 * self.scale, offset.1 * self.scale);
+        // inwards means right/down from the left/top edges and left/up from the right/bottom ones
+        let sign = |f: f32| if f > 0.75 { -1.0 } else { 1.0 };
+        let x = (self.width - size.0) as f32 * fx + ox * sign(fx);
+        let y = (self.height - size.1) as f32 * fy + oy * sign(fy);
+        Rect { x: x as i32, y: y as i32, w: size.0, h: size.1 }
+    }
+
+    pub fn place(&self, anchor: Anchor, offset: (f32, f32), size: (f32, f32)) -> Rect {
+        self.place_px(anchor, offset, (self.px(size.0), self.px(size.1)))
+    }
+
+    // texture `id` scaled to `height`, keeping its aspect ratio
+    pub fn image(&self, fb: &mut Framebuffer, tex: &TextureManager, anchor: Anchor, offset: (f32, f32), height: f32, id: char) -> Rect {
+        let (tw, th) = tex.get_size(id);
+        let h = self.px(height);
+        let w = ((h as f32 * tw as f32 / th.max(1) as f32) as i32).max(1);
+        let rect = self.place_px(anchor, offset, (w, h));
+        blit(fb, tex, id, rect);
+        rect
+    }
+
+    // horizontal bar filled to `value` in 0..=1
+    pub fn bar(&self, fb: &mut Framebuffer, anchor: Anchor, offset: (f32, f32), size: (f32, f32), value: f32, fill: Color, back: Color) -> Rect {
+        let rect = self.place(anchor, offset, size);
+        let filled = (rect.w as f32 * value.clamp(0.0, 1.0)) as i32;
+        for y in rect.y..rect.y + rect.h {
+            for x in rect.x..rect.x + rect.w {
+                if x < 0 || y < 0 { continue; }
+                let c = if x - rect.x < filled { fill } else { back };
+                fb.blend_pixel_color(x as u32, y as u32, c);
+            }
+        }
+        rect
+    }
+
+    // row of square slots, each with an optional texture inside
+    pub fn icon_slots(&self, fb: &mut Framebuffer, tex: &TextureManager, anchor: Anchor, offset: (f32, f32), slot: f32, icons: &[Option<char>]) -> Rect {
+        let s = self.px(slot);
+        let gap = self.px(4.0);
+        let n = icons.len() as i32;
+        let rect = self.place_px(anchor, offset, (n * s + (n - 1).max(0) * gap, s));
+        let frame = Color::new(0, 0, 0, 110);
+        for (i, icon) in icons.iter().enumerate() {
+            let slot_rect = Rect { x: rect.x + i as i32 * (s + gap), y: rect.y, w: s, h: s };
+            fill_rect(fb, slot_rect, frame);
+            if let Some(id) = icon { blit(fb, tex, *id, slot_rect); }
+        }
+        rect
+    }
+
+    // `size` is the line height in reference pixels
+    pub fn text(&self, fb: &mut Framebuffer, anchor: Anchor, offset: (f32, f32), size: f32, text: &str, color: Color) -> Rect {
+        let px = size * self.scale;
+        let (w, h) = measure_text(text, px);
+        let rect = self.place_px(anchor, offset, (w, h));
+        let style = TextStyle::new(px, color).shadow(Color::new(0, 0, 0, 160));
+        draw_text(fb, text, rect.x, rect.y, &style);
+        rect
+    }
+}
+
+pub fn fill_rect(fb: &mut Framebuffer, rect: Rect, color: Color) {
+    for y in rect.y.max(0)..rect.y + rect.h {
+        for x in rect.x.max(0)..rect.x + rect.w {
+            fb.blend_pixel_color(x as u32, y as u32, color);
+        }
+    }
+}
+
+// scales texture `id` into `rect`; magenta is the transparent colour of the sprites
+pub fn blit(fb: &mut Framebuffer, tex: &TextureManager, id: char, rect: Rect) {
+    let (tw, th) = tex.get_size(id);
+    if tw == 0 || th == 0 || rect.w <= 0 || rect.h <= 0 { return; }
+    for dy in 0..rect.h {
+        let ty = (dy as f32 / rect.h as f32 * th as f32) as u32;
+        for dx in 0..rect.w {
+            let tx = (dx as f32 / rect.w as f32 * tw as f32) as u32;
+            let c = tex.sample(id, tx, ty);
+            if (c.r, c.g, c.b) == (255, 0, 255) { continue; }
+            let (x, y) = (rect.x + dx, rect.y + dy);
+            if x >= 0 && y >= 0 { fb.blend_pixel_color(x as u32, y as u32, c); }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use raylib::prelude::*;
+
+// Player controls sampled once per simulation tick, so the game logic never reads the window
+// directly and the same inputs always produce the same movement.
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct PlayerInput {
+    pub forward: bool,
+    pub back: bool,
+    pub left: bool,
+    pub right: bool,
+    pub sprint: bool,
+    pub crouch: bool,
+    pub mouse_dx: f32, // horizontal mouse movement accumulated since the previous tick
+}
+
+impl PlayerInput {
+    // Reads the movement keys (shift sprints, control crouches), the mouse delta is added by the caller
+    pub fn read_keys(rl: &RaylibHandle) -> Self {
+        PlayerInput {
+            forward: rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP),
+            back: rl.is_key_down(KeyboardKey::KEY_S) || rl.is_key_down(KeyboardKey::KEY_DOWN),
+            right: rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT),
+            left: rl.is_key_down(KeyboardKey::KEY_A) || rl.is_key_down(KeyboardKey::KEY_LEFT),
+            sprint: rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT),
+            crouch: rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL),
+            mouse_dx: 0.0,
+        }
+    }
+}
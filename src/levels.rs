@@ -1,41 +1,153 @@
 use crate::maze::{Maze, load_maze};
 use crate::sprites::Enemy;
+use crate::fog::Fog;
+use crate::mazegen::endless_level;
+use crate::postfx::PostEffect;
+use raylib::prelude::Color;
 
 // class that defines multiples levels on the game
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum GameState {
     Start,
     Playing,
     Win,
     GameOver,
+    Editor,
+}
+
+// what the floor of a level is made of, it picks the footstep sounds
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Surface {
+    Ice,
+    Snow,
+    Stone,
 }
 
 //this struct defines the level that will be loaded
-//recibe the name of the level, the maze path, the player start position and the enemies positions
+//recibe the name of the level, the maze path, the player start position, the enemies positions
+//the fog used to darken or tint the world with the distance, the post effects of the level
+//and the floor surface
 
 pub struct LevelDef {
     pub name: &'static str,
     pub maze_path: &'static str,
     pub player_start: (f32, f32, f32),
     pub enemies: &'static [(f32, f32, char)],
+    pub fog: Fog,
+    pub post: &'static [PostEffect],
+    pub floor: Surface,
 }
 
 pub const LEVELS: &[LevelDef] = &[
     LevelDef { name: "Nivel 1", maze_path: "maze.txt", player_start: (13.0, 2.0, 0.0), enemies: &[
-        (14.0, 4.0, 'e'), (2.5, 9.5, 'f'), (4.5, 1.5, 'k'), (6.5, 1.5, 'p'), ] },
+        (14.0, 4.0, 'e'), (2.5, 9.5, 'f'), (4.5, 1.5, 'k'), (6.5, 1.5, 'p'), ],
+        fog: Fog::exponential(Color::BLACK, 0.0, 0.0015, 0.3),
+        post: &[PostEffect::Vignette { strength: 0.7 }, PostEffect::Grain { amount: 0.08 }],
+        floor: Surface::Ice },
     LevelDef { name: "Nivel 2", maze_path: "maze2.txt", player_start: (13.0, 2.0, 0.0), enemies: &[
-        (11.5, 2.5, 'e'), (2.5, 7.5, 'f'), (2.5, 4.5, 'k'), (4.5, 4.5, 'p')] },
+        (11.5, 2.5, 'e'), (2.5, 7.5, 'f'), (2.5, 4.5, 'k'), (4.5, 4.5, 'p')],
+        fog: Fog::linear(Color::new(90, 100, 120, 255), 64.0, 640.0, 0.8),
+        post: &[
+            PostEffect::Grade { saturation: 0.6, contrast: 1.1, tint: Color::new(215, 230, 255, 255) },
+            PostEffect::Scanlines { intensity: 0.25, spacing: 3 },
+            PostEffect::Vignette { strength: 0.5 },
+        ],
+        floor: Surface::Snow },
     LevelDef { name: "Nivel 3", maze_path: "maze3.txt", player_start: (15.0, 1.5, 0.0), enemies: &[
-        (14.5, 1.5, 'e'), (14.0, 6.5, 'f'), (3.0, 2.5, 'k'), (8.5, 7.5, 'p'), ] },
+        (14.5, 1.5, 'e'), (14.0, 6.5, 'f'), (3.0, 2.5, 'k'), (8.5, 7.5, 'p'), ],
+        fog: Fog::linear(Color::new(200, 215, 230, 255), 192.0, 1600.0, 0.4),
+        post: &[
+            PostEffect::Grade { saturation: 0.4, contrast: 1.2, tint: Color::new(255, 245, 235, 255) },
+            PostEffect::ChromaticOffset { pixels: 2 },
+            PostEffect::Grain { amount: 0.12 },
+            PostEffect::Vignette { strength: 0.4 },
+        ],
+        floor: Surface::Stone },
 ];
 
+// The endless mode uses the index right after the hand made levels, its maze is generated
+// from the seed of the run
+pub const ENDLESS_LEVEL: usize = LEVELS.len();
+
+pub const ENDLESS: LevelDef = LevelDef {
+    name: "Modo infinito",
+    maze_path: "",
+    player_start: (0.0, 0.0, 0.0),
+    enemies: &[],
+    fog: Fog::exponential(Color::BLACK, 0.0, 0.0018, 0.4),
+    post: &[PostEffect::Vignette { strength: 0.8 }, PostEffect::Grain { amount: 0.1 }],
+    floor: Surface::Ice,
+};
+
+pub fn level_def(level: usize) -> &'static LevelDef {
+    if level < LEVELS.len() { &LEVELS[level] } else { &ENDLESS }
+}
+
 // Load the maze, enemies and player start position for a given level definition
 
+//if the editor saved an entities file for the maze, it replaces the positions of the definition
+
 pub fn load_level(def: &LevelDef, block_size: usize) -> (Maze, Vec<Enemy>, (f32, f32, f32)) {
     let maze = load_maze(def.maze_path);
-    let enemies = def.enemies.iter()
+    let (start, entities) = load_entities(def.maze_path)
+        .unwrap_or_else(|| (def.player_start, def.enemies.to_vec()));
+    let enemies = entities.iter()
+        .map(|(x, y, id)| Enemy::new(*x * block_size as f32, *y * block_size as f32, *id))
+        .collect::<Vec<_>>();
+    (maze, enemies, start)
+}
+
+// Entities file written by the level editor next to the maze (`<maze_path>.entities`),
+// positions are in blocks like in LevelDef:
+//   start <x> <y> <angle>
+//   enemy <id> <x> <y>
+
+pub fn entities_path(maze_path: &str) -> String {
+    format!("{maze_path}.entities")
+}
+
+pub fn load_entities(maze_path: &str) -> Option<((f32, f32, f32), Vec<(f32, f32, char)>)> {
+    let path = entities_path(maze_path);
+    let text = std::fs::read_to_string(&path).ok()?;
+    let mut start = None;
+    let mut entities = Vec::new();
+    for line in text.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let num = |i: usize| parts.get(i).and_then(|v| v.parse::<f32>().ok());
+        match parts.first() {
+            Some(&"start") => match (num(1), num(2), num(3)) {
+                (Some(x), Some(y), Some(a)) => start = Some((x, y, a)),
+                _ => { eprintln!("{path}: línea inválida '{line}'"); return None; }
+            },
+            Some(&"enemy") => match (parts.get(1).and_then(|v| v.chars().next()), num(2), num(3)) {
+                (Some(id), Some(x), Some(y)) => entities.push((x, y, id)),
+                _ => { eprintln!("{path}: línea inválida '{line}'"); return None; }
+            },
+            None => {}
+            _ => { eprintln!("{path}: línea inválida '{line}'"); return None; }
+        }
+    }
+    match start {
+        Some(s) => Some((s, entities)),
+        None => { eprintln!("{path}: falta la posición inicial"); None }
+    }
+}
+
+pub fn save_entities(maze_path: &str, start: (f32, f32, f32), entities: &[(f32, f32, char)]) -> std::io::Result<()> {
+    let mut out = format!("start {} {} {}\n", start.0, start.1, start.2);
+    for (x, y, id) in entities {
+        out.push_str(&format!("enemy {id} {x} {y}\n"));
+    }
+    std::fs::write(entities_path(maze_path), out)
+}
+
+// Same as load_level but by index, the endless level is generated with `seed`
+pub fn load_level_index(level: usize, block_size: usize, seed: u64) -> (Maze, Vec<Enemy>, (f32, f32, f32)) {
+    if level < LEVELS.len() { return load_level(&LEVELS[level], block_size); }
+    let generated = endless_level(seed);
+    let enemies = generated.enemies.iter()
         .map(|(x, y, id)| Enemy::new(*x * block_size as f32, *y * block_size as f32, *id))
         .collect::<Vec<_>>();
-    (maze, enemies, def.player_start)
+    (generated.maze, enemies, generated.player_start)
 }
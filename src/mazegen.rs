@@ -0,0 +1,407 @@
+use crate::maze::Maze;
+
+// Procedural maze generator. It builds a grid of logical cells with passages between them and
+// writes it in the same glyph format as the hand made maze*.txt files: every cell is a 2x2 block
+// of floor surrounded by '+' corners, '-' horizontal walls and '|' vertical walls (3 chars per cell
+// plus the closing border), so a WxH grid becomes (3W+1)x(3H+1) blocks.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Algorithm {
+    Backtracker,
+    Prim,
+    Rooms,
+}
+
+pub const ALGORITHMS: [Algorithm; 3] = [Algorithm::Backtracker, Algorithm::Prim, Algorithm::Rooms];
+
+// Small deterministic random generator (splitmix64), the same seed always gives the same maze
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self { Rng(seed) }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // value in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 { return 0; }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, lo: usize, hi: usize) -> usize {
+        lo + self.below(hi.saturating_sub(lo) + 1)
+    }
+}
+
+// Logical maze: which neighbours of every cell are connected
+pub struct Grid {
+    pub w: usize,
+    pub h: usize,
+    east: Vec<bool>,  // passage from (x, y) to (x + 1, y)
+    south: Vec<bool>, // passage from (x, y) to (x, y + 1)
+}
+
+impl Grid {
+    fn new(w: usize, h: usize) -> Self {
+        Grid { w, h, east: vec![false; w * h], south: vec![false; w * h] }
+    }
+
+    fn idx(&self, x: usize, y: usize) -> usize { y * self.w + x }
+
+    // opens the passage between two neighbouring cells
+    fn open(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let (x0, y0) = (a.0.min(b.0), a.1.min(b.1));
+        let i = self.idx(x0, y0);
+        if a.1 == b.1 { self.east[i] = true; } else { self.south[i] = true; }
+    }
+
+    fn neighbours(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut n = Vec::with_capacity(4);
+        if x > 0 { n.push((x - 1, y)); }
+        if y > 0 { n.push((x, y - 1)); }
+        if x + 1 < self.w { n.push((x + 1, y)); }
+        if y + 1 < self.h { n.push((x, y + 1)); }
+        n
+    }
+
+    // neighbours that can be reached from (x, y)
+    pub fn passages(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut n = Vec::with_capacity(4);
+        if x > 0 && self.east[self.idx(x - 1, y)] { n.push((x - 1, y)); }
+        if y > 0 && self.south[self.idx(x, y - 1)] { n.push((x, y - 1)); }
+        if x + 1 < self.w && self.east[self.idx(x, y)] { n.push((x + 1, y)); }
+        if y + 1 < self.h && self.south[self.idx(x, y)] { n.push((x, y + 1)); }
+        n
+    }
+
+    // steps from `start` to every cell (usize::MAX if unreachable)
+    pub fn distances(&self, start: (usize, usize)) -> Vec<usize> {
+        let mut dist = vec![usize::MAX; self.w * self.h];
+        let mut queue = std::collections::VecDeque::new();
+        dist[self.idx(start.0, start.1)] = 0;
+        queue.push_back(start);
+        while let Some((x, y)) = queue.pop_front() {
+            let d = dist[self.idx(x, y)];
+            for (nx, ny) in self.passages(x, y) {
+                let i = self.idx(nx, ny);
+                if dist[i] == usize::MAX {
+                    dist[i] = d + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        dist
+    }
+
+    // Writes the grid with the '+', '-' and '|' glyphs
+    pub fn to_maze(&self) -> Maze {
+        let (cols, rows) = (self.w * 3 + 1, self.h * 3 + 1);
+        let mut maze = vec![vec![' '; cols]; rows];
+        for y in 0..=self.h {
+            for x in 0..=self.w {
+                maze[y * 3][x * 3] = '+';
+            }
+        }
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let (bx, by) = (x * 3, y * 3);
+                // top wall
+                if y == 0 || !self.south[self.idx(x, y - 1)] {
+                    maze[by][bx + 1] = '-';
+                    maze[by][bx + 2] = '-';
+                }
+                // left wall
+                if x == 0 || !self.east[self.idx(x - 1, y)] {
+                    maze[by + 1][bx] = '|';
+                    maze[by + 2][bx] = '|';
+                }
+            }
+            // right border
+            maze[y * 3 + 1][self.w * 3] = '|';
+            maze[y * 3 + 2][self.w * 3] = '|';
+        }
+        for x in 0..self.w {
+            maze[self.h * 3][x * 3 + 1] = '-';
+            maze[self.h * 3][x * 3 + 2] = '-';
+        }
+        maze
+    }
+}
+
+// Depth first search that carves long winding corridors
+fn backtracker(w: usize, h: usize, rng: &mut Rng) -> Grid {
+    let mut grid = Grid::new(w, h);
+    let mut visited = vec![false; w * h];
+    let start = (rng.below(w), rng.below(h));
+    let mut stack = vec![start];
+    visited[grid.idx(start.0, start.1)] = true;
+    while let Some(&(x, y)) = stack.last() {
+        let options: Vec<(usize, usize)> = grid.neighbours(x, y)
+            .into_iter()
+            .filter(|&(nx, ny)| !visited[grid.idx(nx, ny)])
+            .collect();
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = options[rng.below(options.len())];
+        grid.open((x, y), next);
+        visited[grid.idx(next.0, next.1)] = true;
+        stack.push(next);
+    }
+    grid
+}
+
+// Randomized Prim: grows the maze from a frontier, gives many short dead ends
+fn prim(w: usize, h: usize, rng: &mut Rng) -> Grid {
+    let mut grid = Grid::new(w, h);
+    let mut in_maze = vec![false; w * h];
+    let start = (rng.below(w), rng.below(h));
+    in_maze[grid.idx(start.0, start.1)] = true;
+    let mut frontier: Vec<((usize, usize), (usize, usize))> =
+        grid.neighbours(start.0, start.1).into_iter().map(|n| (start, n)).collect();
+    while !frontier.is_empty() {
+        let (from, to) = frontier.swap_remove(rng.below(frontier.len()));
+        if in_maze[grid.idx(to.0, to.1)] { continue; }
+        grid.open(from, to);
+        in_maze[grid.idx(to.0, to.1)] = true;
+        for n in grid.neighbours(to.0, to.1) {
+            if !in_maze[grid.idx(n.0, n.1)] { frontier.push((to, n)); }
+        }
+    }
+    grid
+}
+
+// Open rooms joined by L shaped corridors, every room connects to the previous one
+fn rooms(w: usize, h: usize, rng: &mut Rng) -> Grid {
+    let mut grid = Grid::new(w, h);
+    let mut centers: Vec<(usize, usize)> = Vec::new();
+    let attempts = (w * h / 6).max(3);
+    for _ in 0..attempts {
+        let rw = rng.range(2, 3.min(w));
+        let rh = rng.range(2, 3.min(h));
+        let x0 = rng.below(w - rw + 1);
+        let y0 = rng.below(h - rh + 1);
+        for y in y0..y0 + rh {
+            for x in x0..x0 + rw {
+                if x + 1 < x0 + rw { grid.open((x, y), (x + 1, y)); }
+                if y + 1 < y0 + rh { grid.open((x, y), (x, y + 1)); }
+            }
+        }
+        centers.push((x0 + rw / 2, y0 + rh / 2));
+    }
+    for pair in centers.windows(2) {
+        let (mut x, mut y) = pair[0];
+        let (tx, ty) = pair[1];
+        let horizontal_first = rng.below(2) == 0;
+        for phase in 0..2 {
+            if (phase == 0) == horizontal_first {
+                while x != tx {
+                    let nx = if tx > x { x + 1 } else { x - 1 };
+                    grid.open((x, y), (nx, y));
+                    x = nx;
+                }
+            } else {
+                while y != ty {
+                    let ny = if ty > y { y + 1 } else { y - 1 };
+                    grid.open((x, y), (x, ny));
+                    y = ny;
+                }
+            }
+        }
+    }
+    // cells that no room or corridor touched are joined to a reached neighbour, one at a time,
+    // until nothing is sealed off
+    loop {
+        let dist = grid.distances(centers[0]);
+        let unreached = (0..w * h).map(|i| (i % w, i / w)).find_map(|(x, y)| {
+            if dist[grid.idx(x, y)] != usize::MAX { return None; }
+            let reached: Vec<(usize, usize)> = grid.neighbours(x, y)
+                .into_iter()
+                .filter(|&(nx, ny)| dist[grid.idx(nx, ny)] != usize::MAX)
+                .collect();
+            if reached.is_empty() { None } else { Some(((x, y), reached)) }
+        });
+        let Some((cell, reached)) = unreached else { break; };
+        let target = reached[rng.below(reached.len())];
+        grid.open(cell, target);
+    }
+    grid
+}
+
+pub fn generate_grid(algorithm: Algorithm, w: usize, h: usize, rng: &mut Rng) -> Grid {
+    let (w, h) = (w.max(3), h.max(3));
+    match algorithm {
+        Algorithm::Backtracker => backtracker(w, h, rng),
+        Algorithm::Prim => prim(w, h, rng),
+        Algorithm::Rooms => rooms(w, h, rng),
+    }
+}
+
+// A generated level in the same units as `LevelDef` (blocks)
+pub struct GeneratedLevel {
+    pub maze: Maze,
+    pub player_start: (f32, f32, f32),
+    pub enemies: Vec<(f32, f32, char)>,
+}
+
+// center of a logical cell in blocks
+fn cell_center(x: usize, y: usize) -> (f32, f32) {
+    (x as f32 * 3.0 + 2.0, y as f32 * 3.0 + 2.0)
+}
+
+// Builds a playable level: the goal 'g' goes in the cell farthest from the start (always
+// reachable because every generator returns a connected maze), the key about two thirds of the
+// way there and the chasers ('f') far enough from the start to not catch the player right away.
+pub fn generate_level(algorithm: Algorithm, w: usize, h: usize, seed: u64) -> GeneratedLevel {
+    let mut rng = Rng::new(seed);
+    let grid = generate_grid(algorithm, w, h, &mut rng);
+    let (w, h) = (grid.w, grid.h);
+    let mut maze = grid.to_maze();
+
+    let start = (rng.below(w), rng.below(h));
+    let dist = grid.distances(start);
+    let max_d = dist.iter().copied().filter(|d| *d != usize::MAX).max().unwrap_or(0);
+    let goal_i = dist.iter().position(|d| *d == max_d).unwrap_or(0);
+    let goal = (goal_i % w, goal_i / w);
+
+    // 'g' takes one of the four floor blocks of the goal cell, the other three stay walkable
+    maze[goal.1 * 3 + 1][goal.0 * 3 + 2] = 'g';
+
+    // face the first open passage
+    let angle = match grid.passages(start.0, start.1).first() {
+        Some(&(nx, _)) if nx > start.0 => 0.0,
+        Some(&(nx, _)) if nx < start.0 => std::f32::consts::PI,
+        Some(&(_, ny)) if ny > start.1 => std::f32::consts::FRAC_PI_2,
+        Some(_) => -std::f32::consts::FRAC_PI_2,
+        None => 0.0,
+    };
+    let (sx, sy) = cell_center(start.0, start.1);
+
+    // candidate cells ordered by how close they are to a wanted distance from the start
+    let mut taken = vec![start, goal];
+    let pick = |rng: &mut Rng, min_frac: f32, max_frac: f32, taken: &mut Vec<(usize, usize)>| {
+        let lo = (max_d as f32 * min_frac) as usize;
+        let hi = ((max_d as f32 * max_frac) as usize).max(lo);
+        let cells: Vec<(usize, usize)> = (0..w * h)
+            .filter(|i| dist[*i] != usize::MAX && dist[*i] >= lo && dist[*i] <= hi)
+            .map(|i| (i % w, i / w))
+            .filter(|c| !taken.contains(c))
+            .collect();
+        let cell = if cells.is_empty() {
+            (0..w * h).map(|i| (i % w, i / w)).find(|c| !taken.contains(c))?
+        } else {
+            cells[rng.below(cells.len())]
+        };
+        taken.push(cell);
+        Some(cell)
+    };
+
+    let mut enemies = Vec::new();
+    if let Some((x, y)) = pick(&mut rng, 0.5, 0.8, &mut taken) {
+        let (cx, cy) = cell_center(x, y);
+        enemies.push((cx, cy, 'k'));
+    }
+    let chasers = (w * h / 30).max(1);
+    for _ in 0..chasers {
+        if let Some((x, y)) = pick(&mut rng, 0.45, 1.0, &mut taken) {
+            let (cx, cy) = cell_center(x, y);
+            enemies.push((cx, cy, 'f'));
+        }
+    }
+    for id in ['e', 'p'] {
+        if let Some((x, y)) = pick(&mut rng, 0.2, 0.9, &mut taken) {
+            let (cx, cy) = cell_center(x, y);
+            enemies.push((cx, cy, id));
+        }
+    }
+
+    GeneratedLevel { maze, player_start: (sx, sy, angle), enemies }
+}
+
+// Level for the endless mode: algorithm and size come from the seed
+pub fn endless_level(seed: u64) -> GeneratedLevel {
+    let mut rng = Rng::new(seed ^ 0x5CA7_C1B0);
+    let algorithm = ALGORITHMS[rng.below(ALGORITHMS.len())];
+    let w = rng.range(5, 9);
+    let h = rng.range(4, 7);
+    generate_level(algorithm, w, h, rng.next_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // floor blocks reachable from `from` walking through ' '
+    fn reachable(maze: &Maze, from: (usize, usize)) -> Vec<Vec<bool>> {
+        let mut seen: Vec<Vec<bool>> = maze.iter().map(|row| vec![false; row.len()]).collect();
+        let mut stack = vec![from];
+        seen[from.1][from.0] = true;
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))] {
+                if maze.get(ny).and_then(|row| row.get(nx)) == Some(&' ') && !seen[ny][nx] {
+                    seen[ny][nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        seen
+    }
+
+    fn check_playable(level: &GeneratedLevel, what: &str) {
+        let maze = &level.maze;
+        let (sx, sy, _) = level.player_start;
+        let start = (sx as usize, sy as usize);
+        assert_eq!(maze[start.1][start.0], ' ', "{what}: start inside a wall");
+        let seen = reachable(maze, start);
+
+        let goals: Vec<(usize, usize)> = maze.iter().enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().filter(|(_, c)| **c == 'g').map(move |(x, _)| (x, y)))
+            .collect();
+        assert_eq!(goals.len(), 1, "{what}: one goal");
+        let (gx, gy) = goals[0];
+        let touch = [(gx + 1, gy), (gx - 1, gy), (gx, gy + 1), (gx, gy - 1)];
+        assert!(touch.iter().any(|&(x, y)| seen[y][x]), "{what}: goal not reachable");
+
+        assert!(level.enemies.iter().any(|e| e.2 == 'k'), "{what}: no key");
+        assert!(level.enemies.iter().any(|e| e.2 == 'f'), "{what}: no chaser");
+        for &(x, y, id) in &level.enemies {
+            assert!(seen[y as usize][x as usize], "{what}: '{id}' at {x},{y} not reachable");
+        }
+    }
+
+    fn same_level(a: &GeneratedLevel, b: &GeneratedLevel) -> bool {
+        a.maze == b.maze && a.player_start == b.player_start && a.enemies == b.enemies
+    }
+
+    #[test]
+    fn same_seed_same_level() {
+        for algorithm in ALGORITHMS {
+            for seed in [0, 1, 42, u64::MAX] {
+                let (a, b) = (generate_level(algorithm, 7, 5, seed), generate_level(algorithm, 7, 5, seed));
+                assert!(same_level(&a, &b), "{algorithm:?} seed {seed}");
+            }
+            let differ = (1..10).any(|seed| !same_level(&generate_level(algorithm, 7, 5, 0), &generate_level(algorithm, 7, 5, seed)));
+            assert!(differ, "{algorithm:?} ignores the seed");
+        }
+        assert!(same_level(&endless_level(7), &endless_level(7)));
+    }
+
+    #[test]
+    fn every_seed_is_playable() {
+        for algorithm in ALGORITHMS {
+            for seed in 0..100 {
+                check_playable(&generate_level(algorithm, 6, 5, seed), &format!("{algorithm:?} seed {seed}"));
+            }
+        }
+        for seed in 0..100 {
+            check_playable(&endless_level(seed), &format!("endless seed {seed}"));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::maze::Maze;
+use crate::player::Player;
+use crate::sprites::Enemy;
+
+// function that converts a maze cell to a color for minimap rendering
+pub fn cell_to_color(cell: char) -> Color {
+  match cell {
+    '+' => {
+      return Color::LAVENDER;
+    },
+    '-' => {
+      return Color::LIGHTBLUE;
+    },
+    '|' => {
+      return Color::WHITE;
+    },
+    'g' => {
+      return Color::GREEN;
+    },
+    _ => {
+      return Color::WHITE;
+    },
+  }
+}
+
+//function that draws a cell of the maze on the framebuffer
+
+pub fn draw_cell(
+  framebuffer: &mut Framebuffer,
+  xo: usize,
+  yo: usize,
+  block_size: usize,
+  cell: char,
+) {
+  if cell == ' ' {
+    return;
+  }
+  let color = cell_to_color(cell);
+  framebuffer.set_current_color(color);
+
+  for x in xo..xo + block_size {
+    for y in yo..yo + block_size {
+      framebuffer.set_pixel(x as u32, y as u32);
+    }
+  }
+}
+
+
+
+// what the minimap draws on top of the walls
+#[derive(Copy, Clone)]
+pub struct MinimapOptions {
+    pub show_cone: bool,     // field of view from the rays of the last frame
+    pub show_entities: bool, // enemies and items in sight
+    pub max_cells: u32,      // the view scrolls around the player when the maze is wider/taller than this
+}
+
+impl MinimapOptions {
+    pub fn new() -> Self {
+        MinimapOptions { show_cone: true, show_entities: true, max_cells: 21 }
+    }
+}
+
+// icon colour for each sprite id, None for ids that aren't shown
+fn entity_color(id: char) -> Option<Color> {
+    match id {
+        'e' | 'f' => Some(Color::RED),
+        'k' => Some(Color::GOLD),
+        'p' => Some(Color::PINK),
+        _ => None,
+    }
+}
+
+//this renders the minimap on to the framebuffer
+//`depth_buffer` holds the distance along the ray of each screen column from render_world, the
+//cone and the visibility of the sprites come from it.
+
+pub fn render_minimap(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    block_size: usize,
+    player: &Player,
+    enemies: &[Enemy],
+    depth_buffer: &[f32],
+    origin_x: u32,
+    origin_y: u32,
+    cell_px: u32,
+    opts: &MinimapOptions,
+) {
+    let bs = block_size as f32;
+    let rows = maze.len() as i32;
+    let cols = maze.iter().map(|r| r.len()).max().unwrap_or(0) as i32;
+    let view_w = cols.min(opts.max_cells as i32);
+    let view_h = rows.min(opts.max_cells as i32);
+
+    // first visible cell, centered on the player and clamped to the maze
+    let px_cell = player.pos.x / bs;
+    let py_cell = player.pos.y / bs;
+    let first_x = (px_cell - view_w as f32 * 0.5).clamp(0.0, (cols - view_w) as f32);
+    let first_y = (py_cell - view_h as f32 * 0.5).clamp(0.0, (rows - view_h) as f32);
+    let width = (view_w as u32) * cell_px;
+    let height = (view_h as u32) * cell_px;
+
+    // map position in blocks -> minimap pixel, None outside the view
+    let to_map = |mx: f32, my: f32| -> Option<(u32, u32)> {
+        let x = (mx - first_x) * cell_px as f32;
+        let y = (my - first_y) * cell_px as f32;
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 { return None; }
+        Some((origin_x + x as u32, origin_y + y as u32))
+    };
+
+    // cells are sampled per pixel so the scroll is smooth
+    for y in 0..height {
+        let my = first_y + y as f32 / cell_px as f32;
+        let row = match maze.get(my as usize) { Some(r) => r, None => continue };
+        for x in 0..width {
+            let mx = first_x + x as f32 / cell_px as f32;
+            match row.get(mx as usize) {
+                Some(&cell) if cell != ' ' => framebuffer.set_pixel_color(origin_x + x, origin_y + y, cell_to_color(cell)),
+                _ => {}
+            }
+        }
+    }
+
+    let columns = depth_buffer.len();
+    let ray_angle = |sx: usize| {
+        let cam_x = (2.0 * sx as f32 / columns as f32) - 1.0;
+        player.a + cam_x * (player.fov * 0.5)
+    };
+
+    // the cone follows every 4th ray up to the wall it hit
+    if opts.show_cone && columns > 0 {
+        let cone = Color::new(255, 240, 120, 70);
+        for sx in (0..columns).step_by(4) {
+            let a = ray_angle(sx);
+            let dist = depth_buffer[sx];
+            if !dist.is_finite() { continue; }
+            let len = dist / bs;
+            let steps = (len * cell_px as f32) as i32;
+            for i in 0..steps {
+                let t = i as f32 / cell_px as f32;
+                if let Some((x, y)) = to_map(px_cell + a.cos() * t, py_cell + a.sin() * t) {
+                    framebuffer.blend_pixel_color(x, y, cone);
+                }
+            }
+        }
+    }
+
+    // a sprite is in sight when it falls inside the fov and nothing on its column is closer
+    if opts.show_entities && columns > 0 {
+        let r = (cell_px as i32 / 4).max(1);
+        for e in enemies {
+            let color = match entity_color(e.id) { Some(c) => c, None => continue };
+            let (dx, dy) = (e.pos.x - player.pos.x, e.pos.y - player.pos.y);
+            let mut diff = dy.atan2(dx) - player.a;
+            while diff > std::f32::consts::PI { diff -= 2.0 * std::f32::consts::PI; }
+            while diff < -std::f32::consts::PI { diff += 2.0 * std::f32::consts::PI; }
+            if diff.abs() > player.fov * 0.5 { continue; }
+            let sx = (((diff / (player.fov * 0.5)) + 1.0) * 0.5 * columns as f32) as usize;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist > depth_buffer[sx.min(columns - 1)] { continue; }
+            if let Some((x, y)) = to_map(e.pos.x / bs, e.pos.y / bs) {
+                for oy in -r..=r {
+                    for ox in -r..=r {
+                        // enemies are diamonds, items squares
+                        if matches!(e.id, 'e' | 'f') && ox.abs() + oy.abs() > r { continue; }
+                        let (ix, iy) = (x as i32 + ox, y as i32 + oy);
+                        if ix >= 0 && iy >= 0 { framebuffer.set_pixel_color(ix as u32, iy as u32, color); }
+                    }
+                }
+            }
+        }
+    }
+
+    let (pxm, pym) = match to_map(px_cell, py_cell) { Some(p) => p, None => return };
+
+    // Draw player as a small square
+    for dy in -5..=5 {
+        for dx in -5..=5 {
+            let x = pxm as i32 + dx;
+            let y = pym as i32 + dy;
+            if x >= 0 && y >= 0 {
+                framebuffer.set_pixel_color(x as u32, y as u32, Color::VIOLET);
+            }
+        }
+    }
+
+    // Draw direction line
+    let dir_len = 12;
+    for i in 0..dir_len {
+        let x = pxm as i32 + (player.a.cos() * i as f32) as i32;
+        let y = pym as i32 + (player.a.sin() * i as f32) as i32;
+        if x >= 0 && y >= 0 {
+            framebuffer.set_pixel_color(x as u32, y as u32, Color::YELLOW);
+        }
+    }
+}
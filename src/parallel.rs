@@ -0,0 +1,63 @@
+use std::thread;
+
+// Number of worker threads used by the renderer
+pub fn render_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// Splits `data` into one contiguous chunk per thread (a multiple of `unit` elements, e.g. a whole
+// framebuffer row) and runs `f(first_index, chunk)` on each one. Every element must only depend
+// on its own index, so the result is the same for any number of threads.
+pub fn par_chunks<T: Send>(
+    data: &mut [T],
+    unit: usize,
+    threads: usize,
+    f: impl Fn(usize, &mut [T]) + Sync,
+) {
+    let unit = unit.max(1);
+    let units = data.len().div_ceil(unit);
+    let per = units.div_ceil(threads.max(1)).max(1) * unit;
+    if threads <= 1 || data.len() <= per {
+        f(0, data);
+        return;
+    }
+    let f = &f;
+    thread::scope(|s| {
+        for (i, chunk) in data.chunks_mut(per).enumerate() {
+            s.spawn(move || f(i * per, chunk));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every element gets its global index, whatever the split
+    fn fill(len: usize, unit: usize, threads: usize) -> Vec<usize> {
+        let mut data = vec![usize::MAX; len];
+        par_chunks(&mut data, unit, threads, |first, chunk| {
+            assert_eq!(first % unit, 0, "chunks start on a whole unit");
+            for (i, v) in chunk.iter_mut().enumerate() { *v = first + i; }
+        });
+        data
+    }
+
+    #[test]
+    fn covers_every_element_once() {
+        let expected: Vec<usize> = (0..7 * 5).collect();
+        for threads in 1..=8 {
+            assert_eq!(fill(7 * 5, 5, threads), expected, "threads={threads}");
+        }
+    }
+
+    #[test]
+    fn rows_not_multiple_of_threads() {
+        // 7 rows over 3 and 4 threads leave a shorter last band
+        for threads in [3, 4, 6] {
+            assert_eq!(fill(7 * 5, 5, threads), (0..35).collect::<Vec<_>>());
+        }
+        // and more threads than rows
+        assert_eq!(fill(2 * 3, 3, 5), (0..6).collect::<Vec<_>>());
+    }
+}
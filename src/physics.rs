@@ -0,0 +1,144 @@
+use raylib::prelude::*;
+
+use crate::maze::Maze;
+
+// Contact between round things in the maze. Everything that reacts to touching the player
+// (enemies attacking, items picked up, the goal) uses the same test: the circles overlap and
+// there is no wall between their centres.
+
+// radii in blocks, so they follow the block size
+pub const PLAYER_RADIUS: f32 = 0.19;
+const RADII: [(char, f32); 5] = [
+    ('e', 0.22),
+    ('f', 0.22),
+    ('k', 0.36), // generous, the key is small and easy to walk past
+    ('p', 0.25),
+    ('g', 0.61), // the iglo reacts a bit before the player reaches its wall
+];
+const DEFAULT_RADIUS: f32 = 0.25;
+
+// radius of the entity `id` in world pixels
+pub fn radius(id: char, block_size: usize) -> f32 {
+    let r = RADII.iter().find(|(c, _)| *c == id).map_or(DEFAULT_RADIUS, |(_, r)| *r);
+    r * block_size as f32
+}
+
+pub fn player_radius(block_size: usize) -> f32 {
+    PLAYER_RADIUS * block_size as f32
+}
+
+pub fn circles_overlap(a: Vector2, ra: f32, b: Vector2, rb: f32) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let r = ra + rb;
+    dx * dx + dy * dy < r * r
+}
+
+// true when the segment from `from` to `to` crosses no wall; the cells holding the two ends are
+// not tested, so an entity standing in (or being) a wall cell like the iglo can still be reached
+pub fn line_of_sight(maze: &Maze, block_size: usize, from: Vector2, to: Vector2) -> bool {
+    let bs = block_size as f32;
+    let (x0, y0) = (from.x / bs, from.y / bs);
+    let (x1, y1) = (to.x / bs, to.y / bs);
+    let (mut cx, mut cy) = (x0.floor() as isize, y0.floor() as isize);
+    let (ex, ey) = (x1.floor() as isize, y1.floor() as isize);
+    let (dx, dy) = (x1 - x0, y1 - y0);
+
+    // grid traversal (DDA): distance along the segment, 0..1, to the next vertical/horizontal line
+    let step_x = if dx > 0.0 { 1 } else { -1 };
+    let step_y = if dy > 0.0 { 1 } else { -1 };
+    let delta_x = if dx != 0.0 { (1.0 / dx).abs() } else { f32::INFINITY };
+    let delta_y = if dy != 0.0 { (1.0 / dy).abs() } else { f32::INFINITY };
+    let mut next_x = if dx > 0.0 { (cx as f32 + 1.0 - x0) * delta_x } else { (x0 - cx as f32) * delta_x };
+    let mut next_y = if dy > 0.0 { (cy as f32 + 1.0 - y0) * delta_y } else { (y0 - cy as f32) * delta_y };
+
+    let steps = (ex - cx).abs() + (ey - cy).abs();
+    for _ in 0..steps {
+        if next_x < next_y {
+            cx += step_x;
+            next_x += delta_x;
+        } else {
+            cy += step_y;
+            next_y += delta_y;
+        }
+        if cx == ex && cy == ey { return true; }
+        if cy < 0 || cx < 0 || cy as usize >= maze.len() || cx as usize >= maze[cy as usize].len() { return false; }
+        if maze[cy as usize][cx as usize] != ' ' { return false; }
+    }
+    true
+}
+
+// the two circles touch and can see each other
+pub fn contact(maze: &Maze, block_size: usize, a: Vector2, ra: f32, b: Vector2, rb: f32) -> bool {
+    circles_overlap(a, ra, b, rb) && line_of_sight(maze, block_size, a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BS: usize = 64;
+
+    fn maze() -> Maze {
+        let rows = ["+-----+", "|     |", "|  |  |", "|     |", "+-----+"];
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    // centre of the block (x, y) in world pixels
+    fn at(x: f32, y: f32) -> Vector2 {
+        Vector2::new((x + 0.5) * BS as f32, (y + 0.5) * BS as f32)
+    }
+
+    #[test]
+    fn sight_along_open_rows_and_diagonals() {
+        let m = maze();
+        assert!(line_of_sight(&m, BS, at(1.0, 1.0), at(5.0, 1.0)));
+        assert!(line_of_sight(&m, BS, at(1.0, 3.0), at(5.0, 3.0)));
+        assert!(line_of_sight(&m, BS, at(1.0, 1.0), at(2.0, 3.0)));
+        assert!(line_of_sight(&m, BS, at(2.0, 2.0), at(2.0, 2.0)));
+    }
+
+    #[test]
+    fn walls_block_sight_both_ways() {
+        let m = maze();
+        assert!(!line_of_sight(&m, BS, at(1.0, 2.0), at(5.0, 2.0)));
+        assert!(!line_of_sight(&m, BS, at(5.0, 2.0), at(1.0, 2.0)));
+        assert!(!line_of_sight(&m, BS, at(1.0, 1.0), at(5.0, 3.0)));
+    }
+
+    #[test]
+    fn end_cells_are_not_tested() {
+        // the goal is a wall cell but can still be reached from the floor next to it
+        let m = maze();
+        assert!(line_of_sight(&m, BS, at(2.0, 2.0), at(3.0, 2.0)));
+        assert!(line_of_sight(&m, BS, at(1.0, 1.0), at(0.0, 1.0)));
+    }
+
+    #[test]
+    fn out_of_bounds_has_no_sight() {
+        let m = maze();
+        assert!(!line_of_sight(&m, BS, at(1.0, 1.0), at(9.0, 1.0)));
+        assert!(!line_of_sight(&m, BS, at(1.0, 1.0), at(-3.0, 1.0)));
+    }
+
+    #[test]
+    fn contact_needs_overlap_and_sight() {
+        let m = maze();
+        let (rp, re) = (player_radius(BS), radius('f', BS));
+        let near = at(1.0, 1.0) + Vector2::new(rp + re - 1.0, 0.0);
+        let apart = at(1.0, 1.0) + Vector2::new(rp + re + 1.0, 0.0);
+        assert!(contact(&m, BS, at(1.0, 1.0), rp, near, re));
+        assert!(!contact(&m, BS, at(1.0, 1.0), rp, apart, re));
+        // close enough to touch, but on the other side of the wall
+        let left = at(2.0, 2.0) + Vector2::new(0.45 * BS as f32, 0.0);
+        let right = at(4.0, 2.0) - Vector2::new(0.45 * BS as f32, 0.0);
+        assert!(circles_overlap(left, BS as f32, right, BS as f32));
+        assert!(!contact(&m, BS, left, BS as f32, right, BS as f32));
+    }
+
+    #[test]
+    fn radii_follow_the_block_size() {
+        assert_eq!(radius('g', 100), 61.0);
+        assert_eq!(radius('?', 100), DEFAULT_RADIUS * 100.0);
+        assert_eq!(player_radius(100), PLAYER_RADIUS * 100.0);
+    }
+}
@@ -1,12 +1,65 @@
 use raylib::prelude::*;
 use std::f32::consts::PI;
 use crate::maze::Maze;
-use raylib::audio::{RaylibAudio, Sound};
+use crate::input::PlayerInput;
+use crate::physics;
 
+#[derive(Copy, Clone)]
 pub struct Player {
     pub pos: Vector2,
     pub a: f32,
     pub fov: f32,
+    pub vel: Vector2,    // world pixels per second, eased towards the wanted speed
+    pub stamina: f32,    // 0..MAX_STAMINA, spent sprinting
+    pub winded: bool,    // stamina ran out, no sprint until it recovers to SPRINT_RECOVER
+    pub crouch: f32,     // 0 standing .. 1 crouched, eased so the camera moves smoothly
+    pub noise: f32,      // how far the last tick's movement can be heard, in blocks
+}
+
+// movement tuning, speeds in world pixels per second
+pub const WALK_SPEED: f32 = 60.0;
+const SPRINT_SPEED: f32 = 100.0;
+const CROUCH_SPEED: f32 = 30.0;
+const ACCELERATION: f32 = 400.0;
+const DECELERATION: f32 = 500.0;
+pub const MAX_STAMINA: f32 = 100.0;
+const STAMINA_DRAIN: f32 = 30.0;   // per second of sprint
+const STAMINA_REGEN: f32 = 18.0;   // per second when not sprinting
+const SPRINT_RECOVER: f32 = 30.0;  // stamina needed to sprint again after running out
+const CROUCH_RATE: f32 = 6.0;      // crouch blend per second
+// eye height as a fraction of a wall, standing and fully crouched
+const EYE_STANDING: f32 = 0.5;
+const EYE_CROUCHED: f32 = 0.32;
+// hearing distance (blocks) of moving at walking speed, crouching muffles it
+const WALK_NOISE: f32 = 3.0;
+const CROUCH_NOISE: f32 = 0.4;
+
+impl Player {
+    pub fn new(pos: Vector2, a: f32) -> Self {
+        Player {
+            pos,
+            a,
+            fov: PI / 3.0,
+            vel: Vector2::new(0.0, 0.0),
+            stamina: MAX_STAMINA,
+            winded: false,
+            crouch: 0.0,
+            noise: 0.0,
+        }
+    }
+
+    // camera height used by the renderer, 0.5 is the middle of the walls
+    pub fn eye(&self) -> f32 {
+        EYE_STANDING + (EYE_CROUCHED - EYE_STANDING) * self.crouch
+    }
+
+    pub fn is_crouching(&self) -> bool {
+        self.crouch > 0.5
+    }
+
+    pub fn speed(&self) -> f32 {
+        (self.vel.x * self.vel.x + self.vel.y * self.vel.y).sqrt()
+    }
 }
 
 fn colision (maze: &Maze, block_size: usize, x: f32, y: f32, radius: f32) -> bool {
@@ -30,18 +83,14 @@ fn colision (maze: &Maze, block_size: usize, x: f32, y: f32, radius: f32) -> boo
     true
 }
 
-// function that procces the player events this is called in th emain render loop
-pub fn process_events(player: &mut Player, rl: &RaylibHandle, dt: f32, maze: &Maze, block_size: usize, audio: Option<&mut Sound>) {
-    //Velocities of forward, lateral, rotation and the mouse movement for the player
-    //Change these values to increse or deacrese the movement speed
-    const MOVE_SPEED: f32 = 60.0;
-    const LATERAL_SPEED: f32 = 60.0;
+// function that procces the player events this is called once per simulation tick
+pub fn process_events(player: &mut Player, input: &PlayerInput, dt: f32, maze: &Maze, block_size: usize) {
+    //Change MOUSE_MOVE_SPEED to increse or deacrese the rotation speed
     const MOUSE_MOVE_SPEED: f32 = 0.0025;
-    const PLAYER_RADIUS: f32 = 12.0;
+    let radius = physics::player_radius(block_size);
 
     // Rotación SOLO mouse
-    let md = rl.get_mouse_delta();
-    player.a += md.x * MOUSE_MOVE_SPEED;
+    player.a += input.mouse_dx * MOUSE_MOVE_SPEED;
 
     // Keep the angle within the range of -PI to PI
     if player.a > PI { player.a -= 2.0 * PI; }
@@ -50,56 +99,145 @@ pub fn process_events(player: &mut Player, rl: &RaylibHandle, dt: f32, maze: &Ma
     let forward = Vector2::new(player.a.cos(), player.a.sin());
     let right = Vector2::new(-forward.y, forward.x);
 
-    let mut new_x = player.pos.x;
-    let mut new_y = player.pos.y;
-    let mut moved = false;
+    // wanted direction from the keys (W/S forward and back, A/D strafe), diagonals aren't faster
+    let axis = |pos: bool, neg: bool| pos as i32 as f32 - neg as i32 as f32;
+    let (f, r) = (axis(input.forward, input.back), axis(input.right, input.left));
+    let mut wish = Vector2::new(forward.x * f + right.x * r, forward.y * f + right.y * r);
+    let wish_len = (wish.x * wish.x + wish.y * wish.y).sqrt();
+    if wish_len > 0.0 { wish = Vector2::new(wish.x / wish_len, wish.y / wish_len); }
+    let moving = wish_len > 0.0;
 
-    // Adelante (W / UP)
-    if rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP) {
-        let tx = new_x + forward.x * MOVE_SPEED * dt;
-        let ty = new_y + forward.y * MOVE_SPEED * dt;
-        if colision(maze, block_size, tx, ty, PLAYER_RADIUS) {
-            new_x = tx; new_y = ty;
-        }
-        moved = true;
+    // crouch eases the camera down, it can't be combined with sprint
+    let crouch_target = if input.crouch { 1.0 } else { 0.0 };
+    let crouch_step = CROUCH_RATE * dt;
+    player.crouch += (crouch_target - player.crouch).clamp(-crouch_step, crouch_step);
+
+    if player.winded && player.stamina >= SPRINT_RECOVER { player.winded = false; }
+    let sprinting = input.sprint && moving && !player.is_crouching() && !player.winded;
+    if sprinting {
+        player.stamina = (player.stamina - STAMINA_DRAIN * dt).max(0.0);
+        if player.stamina <= 0.0 { player.winded = true; }
+    } else {
+        player.stamina = (player.stamina + STAMINA_REGEN * dt).min(MAX_STAMINA);
     }
-    // Atrás (S / DOWN)
-    if rl.is_key_down(KeyboardKey::KEY_S) || rl.is_key_down(KeyboardKey::KEY_DOWN) {
-        let tx = new_x - forward.x * MOVE_SPEED * dt;
-        let ty = new_y - forward.y * MOVE_SPEED * dt;
-        if colision(maze, block_size, tx, ty, PLAYER_RADIUS) {
-            new_x = tx; new_y = ty;
-        }
-        moved = true;
+
+    let top_speed = if player.is_crouching() { CROUCH_SPEED } else if sprinting { SPRINT_SPEED } else { WALK_SPEED };
+    let target = Vector2::new(wish.x * top_speed, wish.y * top_speed);
+
+    // ease the velocity towards the target, faster when stopping than when starting
+    let rate = (if moving { ACCELERATION } else { DECELERATION }) * dt;
+    let (dx, dy) = (target.x - player.vel.x, target.y - player.vel.y);
+    let diff = (dx * dx + dy * dy).sqrt();
+    if diff <= rate {
+        player.vel = target;
+    } else {
+        player.vel.x += dx / diff * rate;
+        player.vel.y += dy / diff * rate;
     }
-    // Strafe derecha (D / RIGHT)
-    if rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-        let tx = new_x + right.x * LATERAL_SPEED * dt;
-        let ty = new_y + right.y * LATERAL_SPEED * dt;
-        if colision(maze, block_size, tx, ty, PLAYER_RADIUS) {
-            new_x = tx; new_y = ty;
-        }
-        moved = true;
+
+    // each axis on its own so the player slides along the walls, a blocked axis loses its speed
+    let tx = player.pos.x + player.vel.x * dt;
+    if colision(maze, block_size, tx, player.pos.y, radius) { player.pos.x = tx; } else { player.vel.x = 0.0; }
+    let ty = player.pos.y + player.vel.y * dt;
+    if colision(maze, block_size, player.pos.x, ty, radius) { player.pos.y = ty; } else { player.vel.y = 0.0; }
+
+    // louder the faster the player goes, crouching is almost silent
+    let muffle = if player.is_crouching() { CROUCH_NOISE } else { 1.0 };
+    player.noise = player.speed() / WALK_SPEED * WALK_NOISE * muffle;
+}
+
+// Position, angle and crouch between two ticks, used to render smoothly when the frame rate
+// doesn't match the simulation rate. The crouch moves the camera (`eye()`) and the sprites, so
+// it is interpolated too; the rest (vel, noise, stamina) only feeds the simulation and comes
+// from `cur` as is.
+pub fn lerp_player(prev: &Player, cur: &Player, alpha: f32) -> Player {
+    let mut da = cur.a - prev.a;
+    if da > PI { da -= 2.0 * PI; }
+    if da < -PI { da += 2.0 * PI; }
+    Player {
+        pos: Vector2::new(
+            prev.pos.x + (cur.pos.x - prev.pos.x) * alpha,
+            prev.pos.y + (cur.pos.y - prev.pos.y) * alpha,
+        ),
+        a: prev.a + da * alpha,
+        crouch: prev.crouch + (cur.crouch - prev.crouch) * alpha,
+        ..*cur
     }
-    // Strafe izquierda (A / LEFT)
-    if rl.is_key_down(KeyboardKey::KEY_A) || rl.is_key_down(KeyboardKey::KEY_LEFT) {
-        let tx = new_x - right.x * LATERAL_SPEED * dt;
-        let ty = new_y - right.y * LATERAL_SPEED * dt;
-        if colision(maze, block_size, tx, ty, PLAYER_RADIUS) {
-            new_x = tx; new_y = ty;
-        }
-        moved = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BS: usize = 64;
+    const DT: f32 = 1.0 / 60.0;
+
+    // long open corridor so the player never reaches a wall
+    fn corridor() -> Maze {
+        let wall: Vec<char> = vec!['-'; 80];
+        let mut floor = vec![' '; 80];
+        floor[0] = '|';
+        floor[79] = '|';
+        vec![wall.clone(), floor.clone(), floor, wall]
     }
 
-    if moved {
-        // usar el sonido pasado desde main
-        if let Some(sound) = audio {
-            if !sound.is_playing() {
-                sound.play();
-            }
+    fn run(player: &mut Player, input: &PlayerInput, secs: f32) {
+        let maze = corridor();
+        for _ in 0..(secs / DT).round() as usize {
+            process_events(player, input, DT, &maze, BS);
         }
     }
 
-    player.pos.x = new_x;
-    player.pos.y = new_y;
-}
\ No newline at end of file
+    fn start() -> Player {
+        Player::new(Vector2::new(1.5 * BS as f32, 1.5 * BS as f32), 0.0)
+    }
+
+    const SPRINT: PlayerInput = PlayerInput { forward: true, back: false, left: false, right: false, sprint: true, crouch: false, mouse_dx: 0.0 };
+
+    #[test]
+    fn sprint_drains_stamina_and_is_faster() {
+        let mut p = start();
+        run(&mut p, &SPRINT, 1.0);
+        assert!((p.stamina - (MAX_STAMINA - STAMINA_DRAIN)).abs() < 0.5);
+        assert!(!p.winded);
+        assert!((p.speed() - SPRINT_SPEED).abs() < 0.01);
+    }
+
+    #[test]
+    fn running_out_winds_until_recovered() {
+        let mut p = start();
+        run(&mut p, &SPRINT, MAX_STAMINA / STAMINA_DRAIN + 0.1);
+        assert!(p.winded);
+        assert!(p.stamina < SPRINT_RECOVER);
+        // winded: holding sprint walks and regains stamina
+        run(&mut p, &SPRINT, 0.5);
+        assert!(p.winded);
+        assert!((p.speed() - WALK_SPEED).abs() < 0.01);
+        assert!(p.stamina > 0.0);
+        // sprint is back once SPRINT_RECOVER is reached
+        run(&mut p, &SPRINT, SPRINT_RECOVER / STAMINA_REGEN);
+        assert!(!p.winded);
+    }
+
+    #[test]
+    fn standing_still_regenerates() {
+        let mut p = start();
+        p.stamina = 10.0;
+        run(&mut p, &PlayerInput::default(), 1.0);
+        assert!((p.stamina - (10.0 + STAMINA_REGEN)).abs() < 0.5);
+        run(&mut p, &PlayerInput::default(), 10.0);
+        assert_eq!(p.stamina, MAX_STAMINA);
+        assert_eq!(p.noise, 0.0);
+    }
+
+    #[test]
+    fn crouching_blocks_sprint_and_muffles_steps() {
+        let mut p = start();
+        let input = PlayerInput { crouch: true, ..SPRINT };
+        run(&mut p, &input, 1.0);
+        assert!(p.is_crouching());
+        assert_eq!(p.stamina, MAX_STAMINA);
+        assert!((p.speed() - CROUCH_SPEED).abs() < 0.01);
+        assert!(p.noise < WALK_NOISE);
+    }
+}
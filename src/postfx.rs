@@ -0,0 +1,202 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::parallel;
+use crate::textures::TextureManager;
+
+// CPU post effects applied to the finished 3D image (before the HUD). Every effect only reads its
+// own row, so the frame is split in bands across threads like the renderer does.
+
+#[derive(Clone, Debug)]
+pub enum PostEffect {
+    Vignette { strength: f32 },               // darkens the corners, 0..1
+    Grain { amount: f32 },                    // animated noise, 0..1
+    Scanlines { intensity: f32, spacing: u32 }, // every `spacing`th row darker
+    ChromaticOffset { pixels: i32 },          // red and blue shifted apart horizontally
+    Grade { saturation: f32, contrast: f32, tint: Color }, // colour grading baked into a LUT
+    LutFile(String),                          // 256x16 strip: 16 blue tiles of red (x) by green (y), from --lut
+}
+
+// 3D colour lookup table, sampled with trilinear interpolation
+const LUT_SIZE: usize = 17;
+
+pub struct Lut {
+    table: Vec<[f32; 3]>,
+}
+
+impl Lut {
+    fn build(f: impl Fn([f32; 3]) -> [f32; 3]) -> Self {
+        let n = LUT_SIZE;
+        let mut table = Vec::with_capacity(n * n * n);
+        for b in 0..n {
+            for g in 0..n {
+                for r in 0..n {
+                    let step = 1.0 / (n - 1) as f32;
+                    table.push(f([r as f32 * step, g as f32 * step, b as f32 * step]));
+                }
+            }
+        }
+        Lut { table }
+    }
+
+    pub fn grade(saturation: f32, contrast: f32, tint: Color) -> Self {
+        let tint = [tint.r as f32 / 255.0, tint.g as f32 / 255.0, tint.b as f32 / 255.0];
+        Self::build(|c| {
+            let luma = 0.299 * c[0] + 0.587 * c[1] + 0.114 * c[2];
+            let mut out = [0.0; 3];
+            for i in 0..3 {
+                let v = luma + (c[i] - luma) * saturation;
+                let v = (v - 0.5) * contrast + 0.5;
+                out[i] = (v * tint[i]).clamp(0.0, 1.0);
+            }
+            out
+        })
+    }
+
+    // standard 16x16x16 strip exported by image editors; it is resampled to LUT_SIZE
+    pub fn from_file(path: &str) -> Option<Self> {
+        let tex = match TextureManager::load_cpu_texture(path) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("No se pudo cargar la LUT {path}: {e}");
+                return None;
+            }
+        };
+        if tex.w != 256 || tex.h != 16 {
+            eprintln!("La LUT {path} debe medir 256x16 (mide {}x{})", tex.w, tex.h);
+            return None;
+        }
+        let texel = |r: usize, g: usize, b: usize| -> [f32; 3] {
+            let i = (g * 256 + b * 16 + r) * 4;
+            [tex.pixels[i] as f32 / 255.0, tex.pixels[i + 1] as f32 / 255.0, tex.pixels[i + 2] as f32 / 255.0]
+        };
+        let nearest = |v: f32| ((v * 15.0).round() as usize).min(15);
+        Some(Self::build(|c| texel(nearest(c[0]), nearest(c[1]), nearest(c[2]))))
+    }
+
+    fn sample(&self, c: Color) -> Color {
+        let n = LUT_SIZE;
+        let scale = (n - 1) as f32 / 255.0;
+        let (fr, fg, fb) = (c.r as f32 * scale, c.g as f32 * scale, c.b as f32 * scale);
+        let (r0, g0, b0) = ((fr as usize).min(n - 2), (fg as usize).min(n - 2), (fb as usize).min(n - 2));
+        let (tr, tg, tb) = (fr - r0 as f32, fg - g0 as f32, fb - b0 as f32);
+        let at = |r: usize, g: usize, b: usize| self.table[(b * n + g) * n + r];
+        let lerp = |a: [f32; 3], b: [f32; 3], t: f32| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t];
+        let c00 = lerp(at(r0, g0, b0), at(r0 + 1, g0, b0), tr);
+        let c10 = lerp(at(r0, g0 + 1, b0), at(r0 + 1, g0 + 1, b0), tr);
+        let c01 = lerp(at(r0, g0, b0 + 1), at(r0 + 1, g0, b0 + 1), tr);
+        let c11 = lerp(at(r0, g0 + 1, b0 + 1), at(r0 + 1, g0 + 1, b0 + 1), tr);
+        let v = lerp(lerp(c00, c10, tg), lerp(c01, c11, tg), tb);
+        Color::new((v[0] * 255.0) as u8, (v[1] * 255.0) as u8, (v[2] * 255.0) as u8, c.a)
+    }
+}
+
+// effects of one level plus the damage flash, which gameplay triggers at runtime
+pub struct PostChain {
+    effects: Vec<PostEffect>,
+    luts: Vec<Option<Lut>>, // one per effect, only for Grade / LutFile
+    frame: u32,             // changes the grain every frame
+    flash: f32,             // 0..1, fades out over time
+    flash_color: Color,
+}
+
+impl PostChain {
+    pub fn new(effects: &[PostEffect]) -> Self {
+        let luts = effects.iter().map(|e| match e {
+            PostEffect::Grade { saturation, contrast, tint } => Some(Lut::grade(*saturation, *contrast, *tint)),
+            PostEffect::LutFile(path) => Lut::from_file(path),
+            _ => None,
+        }).collect();
+        PostChain { effects: effects.to_vec(), luts, frame: 0, flash: 0.0, flash_color: Color::RED }
+    }
+
+    pub fn flash(&mut self, color: Color, amount: f32) {
+        self.flash_color = color;
+        self.flash = self.flash.max(amount.clamp(0.0, 1.0));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.flash = (self.flash - dt * 1.5).max(0.0);
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    // with `effects` off (settings) only the flash is drawn, it is gameplay feedback
+    pub fn apply(&self, fb: &mut Framebuffer, threads: usize, effects: bool) {
+        let active: &[PostEffect] = if effects { &self.effects } else { &[] };
+        if active.is_empty() && self.flash <= 0.0 { return; }
+        let w = fb.width as usize;
+        let (cx, cy) = (w as f32 * 0.5, fb.height as f32 * 0.5);
+        let inv_r2 = 1.0 / (cx * cx + cy * cy);
+        let needs_copy = active.iter().any(|e| matches!(e, PostEffect::ChromaticOffset { .. }));
+
+        parallel::par_chunks(fb.pixels_mut(), w, threads, |first, band| {
+            let y0 = first / w;
+            let mut source = if needs_copy { vec![Color::BLACK; w] } else { Vec::new() };
+            for (r, row) in band.chunks_mut(w).enumerate() {
+                let y = y0 + r;
+                for (effect, lut) in active.iter().zip(&self.luts) {
+                    match *effect {
+                        PostEffect::Vignette { strength } => {
+                            let dy = y as f32 - cy;
+                            for (x, p) in row.iter_mut().enumerate() {
+                                let dx = x as f32 - cx;
+                                let d2 = (dx * dx + dy * dy) * inv_r2;
+                                scale(p, 1.0 - strength * d2);
+                            }
+                        }
+                        PostEffect::Grain { amount } => {
+                            for (x, p) in row.iter_mut().enumerate() {
+                                let n = hash(x as u32, y as u32, self.frame);
+                                let v = ((n & 0xff) as f32 / 255.0 - 0.5) * amount * 255.0;
+                                let add = |c: u8| (c as f32 + v).clamp(0.0, 255.0) as u8;
+                                *p = Color::new(add(p.r), add(p.g), add(p.b), p.a);
+                            }
+                        }
+                        PostEffect::Scanlines { intensity, spacing } => {
+                            if spacing > 0 && y % spacing as usize == 0 {
+                                for p in row.iter_mut() { scale(p, 1.0 - intensity); }
+                            }
+                        }
+                        PostEffect::ChromaticOffset { pixels } => {
+                            source.copy_from_slice(row);
+                            for (x, p) in row.iter_mut().enumerate() {
+                                let rx = (x as i32 + pixels).clamp(0, w as i32 - 1) as usize;
+                                let bx = (x as i32 - pixels).clamp(0, w as i32 - 1) as usize;
+                                p.r = source[rx].r;
+                                p.b = source[bx].b;
+                            }
+                        }
+                        PostEffect::Grade { .. } | PostEffect::LutFile(_) => {
+                            if let Some(lut) = lut {
+                                for p in row.iter_mut() { *p = lut.sample(*p); }
+                            }
+                        }
+                    }
+                }
+                if self.flash > 0.0 {
+                    let a = self.flash * 0.6;
+                    let fc = self.flash_color;
+                    let mix = |c: u8, f: u8| (c as f32 + (f as f32 - c as f32) * a) as u8;
+                    for p in row.iter_mut() {
+                        *p = Color::new(mix(p.r, fc.r), mix(p.g, fc.g), mix(p.b, fc.b), p.a);
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn scale(p: &mut Color, k: f32) {
+    let k = k.clamp(0.0, 1.0);
+    p.r = (p.r as f32 * k) as u8;
+    p.g = (p.g as f32 * k) as u8;
+    p.b = (p.b as f32 * k) as u8;
+}
+
+// cheap per-pixel noise, different every frame
+fn hash(x: u32, y: u32, frame: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x8da6_b343) ^ y.wrapping_mul(0xd816_3841) ^ frame.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
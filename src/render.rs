@@ -0,0 +1,276 @@
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::automap::{Explored, reveal_ray};
+use crate::caster::cast_ray_grid;
+use crate::fog::Fog;
+use crate::framebuffer::Framebuffer;
+use crate::maze::Maze;
+use crate::parallel;
+use crate::player::Player;
+use crate::textures::TextureManager;
+
+//flashlight parameters shared by the floor and wall passes
+struct Flashlight {
+  max_dist: f32,
+  max_dist_sq: f32,
+  ambient: f32,
+  inv_cone: f32,
+  inv_flash: f32,
+  hotspot_scale: f32,
+}
+
+impl Flashlight {
+  fn new(block_size: usize) -> Self {
+    let cone_half = PI / 9.0;
+    let max_dist = 7.0 * block_size as f32;
+    Flashlight {
+      max_dist,
+      max_dist_sq: max_dist * max_dist,
+      ambient: 0.05,
+      inv_cone: 1.0 / cone_half,
+      inv_flash: 1.0 / max_dist,
+      hotspot_scale: 1.0 / (max_dist * 0.25),
+    }
+  }
+
+  // angular falloff of the cone, ang_diff is the angle from the player heading
+  fn ang_factor(&self, ang_diff: f32) -> f32 {
+    let mut a = ang_diff;
+    if a > PI { a -= 2.0*PI; }
+    if a < -PI { a += 2.0*PI; }
+    let lin = (1.0 - (a.abs() * self.inv_cone)).clamp(0.0,1.0);
+    lin * lin.sqrt()
+  }
+
+  fn light(&self, ang_factor: f32, dist: f32) -> f32 {
+    if dist > self.max_dist { return self.ambient; }
+    let dist_factor_lin = (1.0 - (dist * self.inv_flash)).clamp(0.0,1.0);
+    let sqrt_d = dist_factor_lin.sqrt();
+    let dist_factor = sqrt_d + (dist_factor_lin - sqrt_d) * 0.4;
+    let hw = 1.0 - (dist * self.hotspot_scale).clamp(0.0,1.0);
+    let hw2 = hw * hw;
+    let hotspot = hw2 * (0.86 + 0.14 * hw) * ang_factor;
+    let core_base = ang_factor * dist_factor;
+    let s1 = core_base.sqrt();
+    let s2 = s1.sqrt();
+    let core = core_base * s2;
+    (self.ambient + (1.0 - self.ambient) * core + 0.35 * hotspot).min(1.0)
+  }
+}
+
+//what the wall pass needs to draw one screen column
+#[derive(Copy, Clone)]
+struct WallColumn {
+  impact: char,
+  dist: f32,
+  top: i32,
+  bottom: i32,
+  tx: u32,
+  ty_step: f32,
+  shade: f32,
+  ray_angle: f32,
+}
+
+const NO_WALL: WallColumn = WallColumn { impact: ' ', dist: f32::INFINITY, top: 0, bottom: -1, tx: 0, ty_step: 0.0, shade: 0.0, ray_angle: 0.0 };
+
+//This function renders the 3d world to the framebuffer and check the colisions
+//Rays are cast in parallel by column, then the rows are split in bands (one per thread) that draw
+//sky, floor and the wall slices crossing them. Each pixel only depends on its own row/column, so
+//the image is the same with any number of threads.
+//When `explored` is given, the cells crossed by the rays are marked as seen for the automap.
+pub fn render_world(
+  framebuffer: &mut Framebuffer,
+  maze: &Maze,
+  block_size: usize,
+  player: &Player,
+  tex: &TextureManager,
+  depth_buffer: &mut [f32],
+  fog: &Fog,
+  threads: usize,
+  explored: Option<&mut Explored>,
+) {
+  let width = framebuffer.width as usize;
+  let height = framebuffer.height as i32;
+  let num_rays = width;
+  let half_h = height / 2;
+  let proj_plane = (num_rays as f32) / (2.0 * (player.fov * 0.5).tan());
+  let light = Flashlight::new(block_size);
+  let eye = player.eye();
+
+  //cast the rays, one column per ray
+  let mut columns = vec![NO_WALL; num_rays];
+  parallel::par_chunks(&mut columns, 1, threads, |first, chunk| {
+    for (i, col) in chunk.iter_mut().enumerate() {
+      let sx = first + i;
+      let cam_x = (2.0 * sx as f32 / num_rays as f32) - 1.0;
+      let ray_angle = player.a + cam_x * (player.fov * 0.5);
+      let inter = cast_ray_grid(maze, player, ray_angle, block_size);
+      if inter.impact == ' ' || inter.distance <= 0.0 { continue; }
+      let dist = inter.distance;
+      let wall_h = (block_size as f32 * proj_plane / dist) as i32;
+      // the horizon stays in the middle, a lower eye shows more wall above it
+      let mut top = half_h - (wall_h as f32 * (1.0 - eye)) as i32;
+      let mut bottom = half_h + (wall_h as f32 * eye) as i32;
+      if top < 0 { top = 0; }
+      if bottom >= height { bottom = height - 1; }
+
+      let (tw, th) = tex.get_size(inter.impact);
+      let mut tx = (inter.wall_x * tw as f32) as u32;
+      if inter.side == 0 && ray_angle.cos() > 0.0 { tx = tw.saturating_sub(1) - tx; }
+      if inter.side == 1 && ray_angle.sin() < 0.0 { tx = tw.saturating_sub(1) - tx; }
+
+      let side_factor = if inter.side == 1 { 0.75 } else { 1.0 };
+      let ang_factor = light.ang_factor(ray_angle - player.a);
+      let shade = (side_factor * light.light(ang_factor, dist)).clamp(light.ambient, 1.0);
+
+      let column_h = (bottom - top).max(1) as f32;
+      *col = WallColumn { impact: inter.impact, dist, top, bottom, tx, ty_step: th as f32 / column_h, shade, ray_angle };
+    }
+  });
+  for (d, col) in depth_buffer.iter_mut().zip(&columns) { *d = col.dist; }
+
+  //every other column is enough to uncover the cells in view
+  if let Some(explored) = explored {
+    let (bx, by) = (player.pos.x / block_size as f32, player.pos.y / block_size as f32);
+    for col in columns.iter().step_by(2) {
+      if col.impact == ' ' { continue; }
+      // cast_ray_grid gives the distance along the ray, so the reveal stops at the wall it hit
+      let len = col.dist / block_size as f32;
+      reveal_ray(explored, bx, by, col.ray_angle, len);
+    }
+  }
+
+  let dir_left_angle = player.a - player.fov * 0.5;
+  let dir_right_angle = player.a + player.fov * 0.5;
+  let dir_left = Vector2::new(dir_left_angle.cos(), dir_left_angle.sin());
+  let dir_right = Vector2::new(dir_right_angle.cos(), dir_right_angle.sin());
+  let px = player.pos.x / block_size as f32;
+  let py = player.pos.y / block_size as f32;
+
+  // Render one sky row
+  let sky_row = |sy: i32, line: &mut [Color]| {
+    let v = sy as f32 / half_h as f32;
+    for (x, out) in line.iter_mut().enumerate() {
+      let u = (player.a / (2.0 * PI)) + (x as f32 / width as f32);
+      *out = fog.mix(tex.sample_sky(u, v), fog.sky_factor(v));
+    }
+  };
+
+  // Render one floor row
+  let floor_row = |sy: i32, line: &mut [Color]| {
+    // the horizon row itself is drawn as the farthest floor row, the rows below may copy it
+    let p = ((sy - half_h) as f32).max(1.0);
+    let row_dist = (eye * height as f32) / p;
+    let row_world = row_dist * block_size as f32;
+
+    let step_x = row_dist * (dir_right.x - dir_left.x) / num_rays as f32;
+    let step_y = row_dist * (dir_right.y - dir_left.y) / num_rays as f32;
+    let mut floor_x = px + row_dist * dir_left.x;
+    let mut floor_y = py + row_dist * dir_left.y;
+
+    let floor_far = row_world > light.max_dist;
+
+    for out in line.iter_mut() {
+      let u = floor_x.fract();
+      let v = floor_y.fract();
+      let mut col = tex.sample_ground(u, v);
+
+      let world_x = floor_x * block_size as f32;
+      let world_y = floor_y * block_size as f32;
+      let dxw = world_x - player.pos.x;
+      let dyw = world_y - player.pos.y;
+      let dist_w_sq = dxw*dxw + dyw*dyw;
+      let l = if floor_far || dist_w_sq > light.max_dist_sq {
+        light.ambient
+      } else {
+        light.light(light.ang_factor(dyw.atan2(dxw) - player.a), dist_w_sq.sqrt())
+      };
+
+      col.r = (col.r as f32 * l) as u8;
+      col.g = (col.g as f32 * l) as u8;
+      col.b = (col.b as f32 * l) as u8;
+      *out = fog.apply(col, row_world);
+
+      floor_x += step_x;
+      floor_y += step_y;
+    }
+  };
+
+  // the floor is computed every FLOOR_STEP rows and copied to the next ones
+  const FLOOR_STEP: i32 = 2;
+
+  let pixels = framebuffer.pixels_mut();
+  parallel::par_chunks(pixels, width, threads, |first, band| {
+    let y0 = (first / width) as i32;
+    let rows = (band.len() / width) as i32;
+
+    // sky and floor
+    for r in 0..rows {
+      let sy = y0 + r;
+      let start = r as usize * width;
+      if sy < half_h {
+        sky_row(sy, &mut band[start..start + width]);
+        continue;
+      }
+      let base = half_h + ((sy - half_h) / FLOOR_STEP) * FLOOR_STEP;
+      if base != sy && base >= y0 {
+        band.copy_within(start - width..start, start);
+      } else {
+        floor_row(base, &mut band[start..start + width]);
+      }
+    }
+
+    //Render the walls
+    for (sx, col) in columns.iter().enumerate() {
+      if col.impact == ' ' { continue; }
+      let lo = col.top.max(y0);
+      let hi = col.bottom.min(y0 + rows - 1);
+      for sy in lo..=hi {
+        let ty = ((sy - col.top) as f32 * col.ty_step) as u32;
+        let mut c = tex.sample(col.impact, col.tx, ty);
+        c.r = (c.r as f32 * col.shade) as u8;
+        c.g = (c.g as f32 * col.shade) as u8;
+        c.b = (c.b as f32 * col.shade) as u8;
+        band[(sy - y0) as usize * width + sx] = fog.apply(c, col.dist);
+      }
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BS: usize = 64;
+
+    fn maze() -> Maze {
+        let rows = ["+-----+", "|     |", "|  +  |", "|     |", "|     |", "+-----+"];
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    fn render(threads: usize, width: u32, height: u32, player: &Player) -> (Vec<Color>, Vec<f32>) {
+        let mut fb = Framebuffer::new(width, height);
+        let mut depth = vec![0.0; width as usize];
+        let fog = Fog::linear(Color::new(10, 10, 20, 255), 64.0, 400.0, 0.5);
+        render_world(&mut fb, &maze(), BS, player, &TextureManager::new(), &mut depth, &fog, threads, None);
+        (fb.pixels().to_vec(), depth)
+    }
+
+    #[test]
+    fn same_image_with_any_thread_count() {
+        let mut player = Player::new(Vector2::new(1.5 * BS as f32, 3.5 * BS as f32), -0.6);
+        // odd heights so the bands split at rows that are not a multiple of FLOOR_STEP
+        for (w, h) in [(96, 61), (80, 67)] {
+            for crouch in [0.0, 1.0] {
+                player.crouch = crouch;
+                let single = render(1, w, h, &player);
+                for threads in [2, 3, 4, 7] {
+                    let multi = render(threads, w, h, &player);
+                    assert!(single.0 == multi.0, "pixels differ with {threads} threads ({w}x{h})");
+                    assert_eq!(single.1, multi.1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::fs;
+use std::io;
+
+use crate::input::PlayerInput;
+use crate::levels::{GameState, ENDLESS_LEVEL};
+use crate::world::World;
+
+// A recorded run: the level, the seed and the input of every simulation tick. Feeding the same
+// inputs to `World::step` at the same tick rate reproduces the run exactly, so a replay file can be
+// attached to a bug report or kept as a regression test.
+//
+// File format (text, one entry per line):
+//   replay 4
+//   level <index in LEVELS, or LEVELS.len() for the endless mode>
+//   seed <u64>
+//   layout <hex>                                 (hash of the maze and entities it was recorded on)
+//   tick <ticks per second>
+//   lives <lives at the start>                   (1 when missing)
+//   i <forward><back><left><right><sprint><crouch> <mouse_dx>
+//                                                (one line per tick, e.g. "i 100110 -2.5")
+//   end <summary>                                (final state written by the recorder)
+
+// Bumped whenever the simulation changes how it reacts to the same input or the summary
+// changes, since older recordings would no longer end where they did.
+// Version 2: acceleration, sprint and crouch. Version 3: layout hash, health and stats in the summary.
+// Version 4: chasers also notice a player they can see.
+pub const REPLAY_VERSION: u32 = 4;
+
+pub struct Replay {
+    pub level: usize,
+    pub seed: u64,
+    pub layout: u64,
+    pub tick_rate: f32,
+    pub lives: u32,
+    pub ticks: Vec<PlayerInput>,
+    pub end: Option<String>,
+}
+
+impl Replay {
+    pub fn new(level: usize, seed: u64, layout: u64, tick_rate: f32, lives: u32) -> Self {
+        Replay { level, seed, layout, tick_rate, lives, ticks: Vec::new(), end: None }
+    }
+
+    pub fn push(&mut self, input: &PlayerInput) {
+        self.ticks.push(*input);
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut out = String::new();
+        out.push_str(&format!("replay {REPLAY_VERSION}\n"));
+        out.push_str(&format!("level {}\n", self.level));
+        out.push_str(&format!("seed {}\n", self.seed));
+        out.push_str(&format!("layout {:016x}\n", self.layout));
+        out.push_str(&format!("tick {}\n", self.tick_rate));
+        out.push_str(&format!("lives {}\n", self.lives));
+        for t in &self.ticks {
+            let flag = |b: bool| if b { '1' } else { '0' };
+            out.push_str(&format!("i {}{}{}{}{}{} {}\n", flag(t.forward), flag(t.back), flag(t.left), flag(t.right), flag(t.sprint), flag(t.crouch), t.mouse_dx));
+        }
+        if let Some(end) = &self.end {
+            out.push_str(&format!("end {end}\n"));
+        }
+        fs::write(path, out)
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let bad = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut replay = Replay::new(0, 0, 0, 60.0, 1);
+        let mut version = None;
+        let mut layout = None;
+        for (n, line) in text.lines().enumerate() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "replay" => {
+                    version = value.parse::<u32>().ok();
+                    // checked right away, older input lines may not even parse
+                    if let Some(v) = version.filter(|v| *v != REPLAY_VERSION) {
+                        return Err(bad(format!("versión de replay no soportada: {v} (actual {REPLAY_VERSION}), la simulación cambió y hay que grabarlo de nuevo")));
+                    }
+                }
+                "level" => replay.level = value.parse().map_err(|_| bad(format!("línea {}: nivel inválido", n + 1)))?,
+                "seed" => replay.seed = value.parse().map_err(|_| bad(format!("línea {}: seed inválida", n + 1)))?,
+                "layout" => layout = Some(u64::from_str_radix(value, 16).map_err(|_| bad(format!("línea {}: layout inválido", n + 1)))?),
+                "tick" => replay.tick_rate = value.parse().map_err(|_| bad(format!("línea {}: tick inválido", n + 1)))?,
+                "lives" => replay.lives = value.parse().map_err(|_| bad(format!("línea {}: vidas inválidas", n + 1)))?,
+                "i" => {
+                    let (keys, dx) = value.split_once(' ').unwrap_or((value, "0"));
+                    let k: Vec<bool> = keys.chars().map(|c| c == '1').collect();
+                    if k.len() != 6 { return Err(bad(format!("línea {}: entrada inválida", n + 1))); }
+                    let mouse_dx = dx.parse().map_err(|_| bad(format!("línea {}: mouse inválido", n + 1)))?;
+                    replay.ticks.push(PlayerInput { forward: k[0], back: k[1], left: k[2], right: k[3], sprint: k[4], crouch: k[5], mouse_dx });
+                }
+                "end" => replay.end = Some(value.to_string()),
+                "" => {}
+                _ => return Err(bad(format!("línea {}: clave desconocida '{key}'", n + 1))),
+            }
+        }
+        match version {
+            Some(_) => {}
+            None => return Err(bad("no es un archivo de replay".to_string())),
+        }
+        replay.layout = layout.ok_or_else(|| bad("falta el layout del nivel".to_string()))?;
+        if replay.level > ENDLESS_LEVEL {
+            return Err(bad(format!("nivel {} no existe", replay.level)));
+        }
+        Ok(replay)
+    }
+}
+
+// `--record` path of one run: the level and seed go before the extension,
+// e.g. "bug.replay" -> "bug-1-123456.replay"
+pub fn run_path(base: &str, level: usize, seed: u64) -> String {
+    let path = std::path::Path::new(base);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => {
+            let name = format!("{}-{level}-{seed}.{}", stem.to_string_lossy(), ext.to_string_lossy());
+            path.with_file_name(name).to_string_lossy().into_owned()
+        }
+        _ => format!("{base}-{level}-{seed}"),
+    }
+}
+
+// Seed for a new run taken from the clock
+pub fn new_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// Hash (FNV-1a) of what the run starts from: the maze, the entities and the player start. The
+// `.entities` files written by the editor can change a level after a replay was recorded.
+pub fn layout_hash(world: &World) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for b in bytes {
+            h ^= *b as u64;
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    for row in &world.maze {
+        for c in row { feed(&(*c as u32).to_le_bytes()); }
+        feed(b"\n");
+    }
+    for e in &world.enemies {
+        feed(&(e.id as u32).to_le_bytes());
+        for v in [e.pos.x, e.pos.y, e.scale] { feed(&v.to_le_bytes()); }
+    }
+    let p = &world.player;
+    for v in [p.pos.x, p.pos.y, p.a] { feed(&v.to_le_bytes()); }
+    h
+}
+
+// Final state of a run as one line, compared when replaying
+pub fn summary(world: &World, state: GameState, ticks: usize) -> String {
+    let p = &world.player;
+    let st = &world.stats;
+    let mut s = format!(
+        "{state:?} ticks={ticks} player={},{},{} stamina={},{} crouch={} key={} health={} lives={} invulnerable={} stats={},{},{},{}",
+        p.pos.x, p.pos.y, p.a, p.stamina, p.winded, p.crouch, world.has_key,
+        world.health, world.lives, world.invulnerable, st.elapsed, st.distance, st.spotted, st.items,
+    );
+    for e in &world.enemies {
+        s.push_str(&format!(" {}={},{}", e.id, e.pos.x, e.pos.y));
+    }
+    s
+}
+
+// Plays a replay without a window or audio and prints the final state.
+// Returns the process exit code: 1 if the file can't be read or the result differs from the
+// recorded one, 0 otherwise.
+pub fn run_headless(path: &str, block_size: usize) -> i32 {
+    let replay = match Replay::load(path) {
+        Ok(r) => r,
+        Err(e) => { eprintln!("No se pudo leer el replay {path}: {e}"); return 1; }
+    };
+    let mut world = World::load(replay.level, block_size, replay.seed);
+    let layout = layout_hash(&world);
+    if layout != replay.layout {
+        eprintln!("El nivel cambió desde que se grabó el replay {path} (layout {layout:016x}, grabado {:016x})", replay.layout);
+        return 1;
+    }
+    world.lives = replay.lives;
+    let dt = 1.0 / replay.tick_rate;
+    let mut state = GameState::Playing;
+    let mut ticks = 0;
+    for input in &replay.ticks {
+        state = world.step(input, dt);
+        world.sounds.clear();
+        ticks += 1;
+        if state != GameState::Playing { break; }
+    }
+    let result = summary(&world, state, ticks);
+    println!("{result}");
+    match &replay.end {
+        Some(expected) if *expected != result => {
+            eprintln!("El replay no coincide con lo grabado:\n  esperado: {expected}\n  obtenido: {result}");
+            1
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("{name}-{}.replay", std::process::id())).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut rec = Replay::new(1, 42, 0xdead_beef, 30.0, 3);
+        rec.push(&PlayerInput { forward: true, sprint: true, mouse_dx: -2.5, ..Default::default() });
+        rec.push(&PlayerInput { back: true, left: true, crouch: true, ..Default::default() });
+        rec.push(&PlayerInput { right: true, mouse_dx: 0.125, ..Default::default() });
+        rec.end = Some("Win ticks=3".to_string());
+        let path = temp_path("round-trip");
+        rec.save(&path).unwrap();
+        let loaded = Replay::load(&path);
+        fs::remove_file(&path).ok();
+        let loaded = loaded.unwrap();
+        assert_eq!((loaded.level, loaded.seed, loaded.layout, loaded.lives), (1, 42, 0xdead_beef, 3));
+        assert_eq!(loaded.tick_rate, 30.0);
+        assert!(loaded.ticks == rec.ticks);
+        assert_eq!(loaded.end, rec.end);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let path = temp_path("old-version");
+        fs::write(&path, "replay 1\nlevel 0\nseed 1\ni 1001 0\n").unwrap();
+        let err = Replay::load(&path).err();
+        fs::remove_file(&path).ok();
+        assert!(err.is_some_and(|e| e.to_string().contains("versión")));
+    }
+
+    // recorded on the first level: walks down to the chaser, loses a life and respawns
+    #[test]
+    fn recorded_run_ends_where_it_did() {
+        assert_eq!(run_headless("tests/replays/level1.replay", 64), 0);
+    }
+}
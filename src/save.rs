@@ -0,0 +1,310 @@
+use std::fs;
+use std::io;
+
+use raylib::prelude::*;
+
+use crate::levels::{LEVELS, ENDLESS_LEVEL};
+use crate::player::{Player, MAX_STAMINA};
+use crate::sprites::Enemy;
+use crate::world::{World, MAX_HEALTH};
+use crate::automap::Explored;
+use crate::stats::RunStats;
+
+// Saved progress: which levels are unlocked and, if the player quit in the middle of a level,
+// the state needed to continue it.
+//
+// File format (text, versioned):
+//   save 5
+//   unlocked <n>                       (levels 0..n can be played)
+//   run <level> <seed> <has_key 0/1>   (only when there is a run to continue)
+//   player <x> <y> <angle> <stamina> <winded 0/1> <crouch> <vel x> <vel y>
+//                                      (the noise is recomputed on the next tick)
+//   stats <elapsed> <distance> <spotted> <items>
+//   health <health> <lives> <invulnerable>
+//   enemy <id> <x> <y> <scale> <alert 0/1> <cooldown>   (one line per enemy / item still in the level)
+//   explored <row of 0/1>              (one line per maze row, cells seen on the automap)
+//
+// Older versions still load, the fields they lack start as in a new run:
+//   1  no stats line, enemies without the alert flag
+//   2  no explored lines, the automap starts empty
+//   3  health line optional (full health, one life) and without the invulnerability time,
+//      enemies without the attack cooldown
+//   4  player without stamina, crouch and velocity (rested, standing, still)
+
+pub const SAVE_PATH: &str = "savegame.txt";
+pub const SAVE_VERSION: u32 = 5;
+
+pub struct SavedRun {
+    pub level: usize,
+    pub seed: u64,
+    pub has_key: bool,
+    pub player: Player,
+    pub enemies: Vec<Enemy>,
+    pub stats: RunStats,
+    pub health: f32,
+    pub lives: u32,
+    pub invulnerable: f32,
+    pub explored: Explored,
+}
+
+impl SavedRun {
+    pub fn capture(world: &World, level: usize) -> Self {
+        SavedRun {
+            level,
+            seed: world.seed,
+            has_key: world.has_key,
+            player: world.player,
+            enemies: world.enemies.clone(),
+            stats: world.stats,
+            health: world.health,
+            lives: world.lives,
+            invulnerable: world.invulnerable,
+            explored: world.explored.clone(),
+        }
+    }
+
+    // Rebuild the world of the level and put everything back where it was
+    pub fn restore(&self, block_size: usize) -> World {
+        let mut world = World::load(self.level, block_size, self.seed);
+        world.player = self.player;
+        world.prev_player = self.player;
+        world.enemies = self.enemies.clone();
+        world.has_key = self.has_key;
+        world.stats = self.stats;
+        world.health = self.health;
+        world.lives = self.lives;
+        world.invulnerable = self.invulnerable;
+        // an empty or mismatched map (old save, edited maze) keeps the fresh one
+        if self.explored.len() == world.explored.len()
+            && self.explored.iter().zip(&world.explored).all(|(a, b)| a.len() == b.len()) {
+            world.explored = self.explored.clone();
+        }
+        world
+    }
+}
+
+pub struct SaveGame {
+    pub unlocked: usize,
+    pub run: Option<SavedRun>,
+}
+
+impl SaveGame {
+    pub fn new() -> Self {
+        SaveGame { unlocked: 1, run: None }
+    }
+
+    // the endless mode is always available
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level < self.unlocked || level == ENDLESS_LEVEL
+    }
+
+    // Completing a level unlocks the next one, the endless mode unlocks nothing
+    pub fn complete_level(&mut self, level: usize) {
+        self.run = None;
+        if level >= LEVELS.len() { return; }
+        self.unlocked = self.unlocked.max((level + 2).min(LEVELS.len()));
+    }
+
+    // Reads the save file, a missing or broken file starts a new game
+    pub fn load_or_new(path: &str) -> Self {
+        match Self::load(path) {
+            Ok(save) => save,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::new(),
+            Err(e) => {
+                eprintln!("No se pudo leer la partida guardada {path}: {e}");
+                Self::new()
+            }
+        }
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let bad = |n: usize, what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("línea {}: {what}", n + 1));
+        let mut save = SaveGame::new();
+        let mut version = None;
+        for (n, line) in text.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let Some(&key) = parts.first() else { continue; };
+            let num = |i: usize| -> io::Result<f32> {
+                parts.get(i).and_then(|v| v.parse::<f32>().ok()).ok_or_else(|| bad(n, "número inválido"))
+            };
+            // fields added by later versions, absent in older files
+            let opt = |i: usize, default: f32| -> io::Result<f32> {
+                if parts.len() > i { num(i) } else { Ok(default) }
+            };
+            match key {
+                "save" => version = parts.get(1).and_then(|v| v.parse::<u32>().ok()),
+                "unlocked" => {
+                    save.unlocked = parts.get(1).and_then(|v| v.parse().ok()).ok_or_else(|| bad(n, "niveles inválidos"))?;
+                }
+                "run" => {
+                    let level: usize = parts.get(1).and_then(|v| v.parse().ok()).ok_or_else(|| bad(n, "nivel inválido"))?;
+                    let seed: u64 = parts.get(2).and_then(|v| v.parse().ok()).ok_or_else(|| bad(n, "seed inválida"))?;
+                    if level > ENDLESS_LEVEL { return Err(bad(n, "el nivel no existe")); }
+                    save.run = Some(SavedRun {
+                        level,
+                        seed,
+                        has_key: parts.get(3) == Some(&"1"),
+                        player: Player::new(Vector2::new(0.0, 0.0), 0.0),
+                        enemies: Vec::new(),
+                        stats: RunStats::default(),
+                        health: MAX_HEALTH,
+                        lives: 1,
+                        invulnerable: 0.0,
+                        explored: Vec::new(),
+                    });
+                }
+                "player" => {
+                    let run = save.run.as_mut().ok_or_else(|| bad(n, "jugador sin partida"))?;
+                    run.player.pos = Vector2::new(num(1)?, num(2)?);
+                    run.player.a = num(3)?;
+                    run.player.stamina = opt(4, run.player.stamina)?.clamp(0.0, MAX_STAMINA);
+                    run.player.winded = opt(5, 0.0)? != 0.0;
+                    run.player.crouch = opt(6, 0.0)?.clamp(0.0, 1.0);
+                    run.player.vel = Vector2::new(opt(7, 0.0)?, opt(8, 0.0)?);
+                }
+                "stats" => {
+                    let run = save.run.as_mut().ok_or_else(|| bad(n, "estadísticas sin partida"))?;
+                    run.stats.elapsed = num(1)?;
+                    run.stats.distance = num(2)?;
+                    run.stats.spotted = num(3)? as u32;
+                    run.stats.items = num(4)? as u32;
+                }
+                "health" => {
+                    let run = save.run.as_mut().ok_or_else(|| bad(n, "salud sin partida"))?;
+                    run.health = num(1)?.clamp(1.0, MAX_HEALTH);
+                    run.lives = (num(2)? as u32).max(1);
+                    run.invulnerable = opt(3, 0.0)?.max(0.0);
+                }
+                "enemy" => {
+                    let run = save.run.as_mut().ok_or_else(|| bad(n, "enemigo sin partida"))?;
+                    let id = parts.get(1).and_then(|v| v.chars().next()).ok_or_else(|| bad(n, "id inválido"))?;
+                    let mut e = Enemy::with_scale(num(2)?, num(3)?, id, num(4)?);
+                    e.prev_pos = e.pos;
+                    e.alert = opt(5, 0.0)? != 0.0;
+                    e.cooldown = opt(6, 0.0)?.max(0.0);
+                    run.enemies.push(e);
+                }
+                "explored" => {
+                    let run = save.run.as_mut().ok_or_else(|| bad(n, "mapa sin partida"))?;
+                    run.explored.push(parts.get(1).unwrap_or(&"").chars().map(|c| c == '1').collect());
+                }
+                _ => return Err(bad(n, "clave desconocida")),
+            }
+        }
+        match version {
+            Some(v) if (1..=SAVE_VERSION).contains(&v) => {}
+            Some(v) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("versión no soportada: {v}"))),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "no es una partida guardada")),
+        }
+        save.unlocked = save.unlocked.clamp(1, LEVELS.len());
+        Ok(save)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut out = format!("save {SAVE_VERSION}\nunlocked {}\n", self.unlocked);
+        if let Some(run) = &self.run {
+            out.push_str(&format!("run {} {} {}\n", run.level, run.seed, if run.has_key { 1 } else { 0 }));
+            let p = &run.player;
+            let winded = if p.winded { 1 } else { 0 };
+            out.push_str(&format!("player {} {} {} {} {winded} {} {} {}\n", p.pos.x, p.pos.y, p.a, p.stamina, p.crouch, p.vel.x, p.vel.y));
+            let st = &run.stats;
+            out.push_str(&format!("stats {} {} {} {}\n", st.elapsed, st.distance, st.spotted, st.items));
+            out.push_str(&format!("health {} {} {}\n", run.health, run.lives, run.invulnerable));
+            for e in &run.enemies {
+                let alert = if e.alert { 1 } else { 0 };
+                out.push_str(&format!("enemy {} {} {} {} {alert} {}\n", e.id, e.pos.x, e.pos.y, e.scale, e.cooldown));
+            }
+            for row in &run.explored {
+                let cells: String = row.iter().map(|&seen| if seen { '1' } else { '0' }).collect();
+                out.push_str(&format!("explored {cells}\n"));
+            }
+        }
+        fs::write(path, out)
+    }
+
+    // Writes the file and reports errors without stopping the game
+    pub fn store(&self, path: &str) {
+        if let Err(e) = self.save(path) {
+            eprintln!("No se pudo guardar la partida en {path}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("{name}-{}.txt", std::process::id())).to_string_lossy().into_owned()
+    }
+
+    fn load_text(name: &str, text: &str) -> io::Result<SaveGame> {
+        let path = temp_path(name);
+        fs::write(&path, text)?;
+        let save = SaveGame::load(&path);
+        fs::remove_file(&path).ok();
+        save
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut player = Player::new(Vector2::new(100.5, 200.25), 1.5);
+        player.stamina = 40.0;
+        player.winded = true;
+        player.crouch = 0.5;
+        player.vel = Vector2::new(-12.0, 3.5);
+        let mut enemy = Enemy::with_scale(64.0, 96.0, 'f', 0.8);
+        enemy.alert = true;
+        enemy.cooldown = 0.25;
+        let run = SavedRun {
+            level: 1,
+            seed: 99,
+            has_key: true,
+            player,
+            enemies: vec![enemy, Enemy::with_scale(10.0, 20.0, 'k', 1.0)],
+            stats: RunStats { elapsed: 12.5, distance: 300.0, spotted: 2, items: 1 },
+            health: 66.0,
+            lives: 2,
+            invulnerable: 0.75,
+            explored: vec![vec![true, false], vec![false, true]],
+        };
+        let save = SaveGame { unlocked: 2, run: Some(run) };
+        let path = temp_path("save-round-trip");
+        save.save(&path).unwrap();
+        let loaded = SaveGame::load(&path);
+        fs::remove_file(&path).ok();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.unlocked, 2);
+        let run = loaded.run.unwrap();
+        assert_eq!((run.level, run.seed, run.has_key), (1, 99, true));
+        let p = &run.player;
+        assert_eq!((p.pos.x, p.pos.y, p.a), (100.5, 200.25, 1.5));
+        assert_eq!((p.stamina, p.winded, p.crouch, p.vel.x, p.vel.y), (40.0, true, 0.5, -12.0, 3.5));
+        assert_eq!((run.stats.elapsed, run.stats.distance, run.stats.spotted, run.stats.items), (12.5, 300.0, 2, 1));
+        assert_eq!((run.health, run.lives, run.invulnerable), (66.0, 2, 0.75));
+        assert_eq!(run.enemies.len(), 2);
+        let e = &run.enemies[0];
+        assert_eq!((e.id, e.pos.x, e.pos.y, e.scale, e.alert, e.cooldown), ('f', 64.0, 96.0, 0.8, true, 0.25));
+        assert_eq!(run.explored, vec![vec![true, false], vec![false, true]]);
+    }
+
+    #[test]
+    fn loads_version_1_with_defaults() {
+        let save = load_text("save-v1", "save 1\nunlocked 2\nrun 0 7 0\nplayer 10 20 0.5\nenemy f 1 2 1\n").unwrap();
+        let run = save.run.unwrap();
+        assert_eq!((run.health, run.lives, run.invulnerable), (MAX_HEALTH, 1, 0.0));
+        assert_eq!((run.player.stamina, run.player.winded, run.player.crouch), (MAX_STAMINA, false, 0.0));
+        assert!(!run.enemies[0].alert);
+        assert!(run.explored.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_other_files() {
+        assert!(load_text("save-future", &format!("save {}\nunlocked 1\n", SAVE_VERSION + 1)).is_err());
+        assert!(load_text("save-none", "unlocked 1\n").is_err());
+        assert!(load_text("save-garbage", "save 1\nunlocked 1\nhola\n").is_err());
+        assert!(load_text("save-orphan", "save 1\nplayer 1 2 3\n").is_err());
+    }
+}
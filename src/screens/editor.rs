@@ -0,0 +1,55 @@
+use raylib::prelude::*;
+
+use crate::editor::{Editor, EditorAction};
+use crate::font::{draw_text, TextStyle};
+use crate::framebuffer::Framebuffer;
+use crate::levels::level_def;
+use crate::render::render_world;
+use crate::sprites::draw_sprites;
+
+use super::{Game, Screen, Transition};
+
+// Level editor: top-down painting or first person preview. The mouse cursor is visible here
+pub struct EditorScreen {
+    editor: Editor,
+}
+
+impl EditorScreen {
+    pub fn new(level: usize, block_size: usize) -> Self {
+        EditorScreen { editor: Editor::open(level, block_size) }
+    }
+}
+
+impl Screen for EditorScreen {
+    fn on_enter(&mut self, _game: &mut Game, rl: &mut RaylibHandle) {
+        rl.enable_cursor();
+    }
+
+    fn on_exit(&mut self, _game: &mut Game, rl: &mut RaylibHandle) {
+        rl.disable_cursor();
+    }
+
+    fn update(&mut self, _game: &mut Game, rl: &mut RaylibHandle, fb: &Framebuffer) -> Transition {
+        match self.editor.update(rl, fb) {
+            EditorAction::Exit => Transition::Pop,
+            EditorAction::None => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, game: &mut Game, _rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let ed = &mut self.editor;
+        fb.clear();
+        let fog = &level_def(ed.level).fog;
+        if let Some((player, enemies)) = ed.preview.as_mut() {
+            render_world(fb, &ed.maze, game.block_size, player, &game.tex, &mut game.depth_buffer, fog, game.render_threads, None);
+            let proj_plane = (fb.width as f32) / (2.0 * (player.fov * 0.5).tan());
+            draw_sprites(fb, player, enemies, &game.tex, &game.depth_buffer, proj_plane, game.block_size, fog, 1.0);
+        } else {
+            ed.draw(fb, &game.tex);
+        }
+        let help_style = TextStyle::new(fb.height as f32 / 36.0, Color::YELLOW).shadow(Color::BLACK);
+        for (i, line) in ed.help_lines().iter().enumerate() {
+            draw_text(fb, line, 10, 10 + (i as f32 * help_style.size * 1.2) as i32, &help_style);
+        }
+    }
+}
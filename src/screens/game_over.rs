@@ -0,0 +1,36 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+
+use super::{Game, MenuLayout, MenuScreen, PlayingScreen, Screen, Transition, draw_menu_image};
+
+// Caught by an enemy: retry the same level or go back to the menu
+pub struct GameOverScreen {
+    level: usize,
+}
+
+impl GameOverScreen {
+    pub fn new(level: usize) -> Self {
+        GameOverScreen { level }
+    }
+}
+
+impl Screen for GameOverScreen {
+    fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, _fb: &Framebuffer) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return Transition::Replace(Box::new(PlayingScreen::new_run(self.level, game)));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            return Transition::Reset(Box::new(MenuScreen::new()));
+        }
+        Transition::None
+    }
+
+    fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let layout = MenuLayout::new(fb, rl);
+        fb.clear_with(Color::BLACK);
+        draw_menu_image(fb, &game.tex, 'o', 0.5);
+        layout.text(fb, "GAME OVER", 400, 70, 70, Color::RED);
+        layout.text(fb, "ENTER: reintentar nivel / M: volver al menú", 300, 160, 30, Color::RAYWHITE);
+    }
+}
@@ -0,0 +1,60 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::levels::{LEVELS, ENDLESS_LEVEL, level_def};
+use crate::stats::format_time;
+
+use super::{EditorScreen, Game, MenuLayout, PlayingScreen, Screen, Transition};
+
+// List of the levels plus the endless mode. ENTER plays, E opens the editor on a hand made level
+pub struct LevelSelectScreen {
+    cursor: usize, // level index, ENDLESS_LEVEL is the last entry
+}
+
+impl LevelSelectScreen {
+    pub fn new() -> Self {
+        LevelSelectScreen { cursor: 0 }
+    }
+}
+
+impl Screen for LevelSelectScreen {
+    fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, _fb: &Framebuffer) -> Transition {
+        let entries = LEVELS.len() + 1;
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) { self.cursor = (self.cursor + 1) % entries; }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) { self.cursor = (self.cursor + entries - 1) % entries; }
+        if rl.is_key_pressed(KeyboardKey::KEY_M) || rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            return Transition::Pop;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_E) && self.cursor < LEVELS.len() {
+            return Transition::Push(Box::new(EditorScreen::new(self.cursor, game.block_size)));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) && game.save_game.is_unlocked(self.cursor) {
+            return Transition::Reset(Box::new(PlayingScreen::new_run(self.cursor, game)));
+        }
+        Transition::None
+    }
+
+    fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let layout = MenuLayout::new(fb, rl);
+        fb.clear_with(Color::BLACK);
+        layout.text(fb, "Selecciona nivel", 60, 40, 60, Color::WHITE);
+
+        for level in 0..=ENDLESS_LEVEL {
+            let y = 160 + level as i32 * 40;
+            let name = level_def(level).name;
+            if !game.save_game.is_unlocked(level) {
+                layout.text(fb, &format!("{name} (bloqueado)"), 80, y, 32, Color::DARKGRAY);
+                continue;
+            }
+            let col = if level == self.cursor { Color::YELLOW } else { Color::GRAY };
+            layout.text(fb, name, 80, y, 32, col);
+        }
+
+        // best time of the highlighted level
+        if let Some(b) = game.best_times.get(level_def(self.cursor).name) {
+            let y = 200 + (ENDLESS_LEVEL as i32 + 1) * 40;
+            layout.text(fb, &format!("Mejor tiempo: {}", format_time(b.best_time)), 80, y, 24, Color::LIGHTGRAY);
+        }
+        layout.text(fb, "UP/DOWN y ENTER   E: editar nivel   M: volver", 60, 860, 20, Color::DARKGRAY);
+    }
+}
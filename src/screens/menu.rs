@@ -0,0 +1,110 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::levels::{ENDLESS_LEVEL, level_def};
+use crate::stats::format_time;
+
+use super::{Game, LevelSelectScreen, MenuLayout, PlayingScreen, Screen, Transition, draw_menu_image};
+
+#[derive(Copy, Clone, PartialEq)]
+enum Entry {
+    Continue,
+    Play,
+    Records,
+    Quit,
+}
+
+// Title screen: continue the saved run, choose a level, see the records or quit
+pub struct MenuScreen {
+    cursor: usize,
+    show_stats: bool,
+}
+
+impl MenuScreen {
+    pub fn new() -> Self {
+        MenuScreen { cursor: 0, show_stats: false }
+    }
+
+    // "Continuar" only shows up when there is a run to continue
+    fn entries(game: &Game) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        if game.save_game.run.is_some() { entries.push(Entry::Continue); }
+        entries.extend([Entry::Play, Entry::Records, Entry::Quit]);
+        entries
+    }
+
+    fn label(entry: Entry, game: &Game) -> String {
+        match entry {
+            Entry::Continue => {
+                let level = game.save_game.run.as_ref().map(|r| r.level).unwrap_or(0);
+                format!("Continuar ({})", level_def(level).name)
+            }
+            Entry::Play => "Jugar".to_string(),
+            Entry::Records => "Récords".to_string(),
+            Entry::Quit => "Salir".to_string(),
+        }
+    }
+}
+
+impl Screen for MenuScreen {
+    fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, _fb: &Framebuffer) -> Transition {
+        let entries = Self::entries(game);
+        if self.cursor >= entries.len() { self.cursor = 0; }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) { self.cursor = (self.cursor + 1) % entries.len(); }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) { self.cursor = (self.cursor + entries.len() - 1) % entries.len(); }
+        if rl.is_key_pressed(KeyboardKey::KEY_S) { self.show_stats = !self.show_stats; }
+        if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) { return Transition::None; }
+
+        match entries[self.cursor] {
+            Entry::Continue => match game.save_game.run.as_ref() {
+                Some(run) => {
+                    if game.settings.record_path.is_some() {
+                        println!("No se graba replay de una partida continuada");
+                    }
+                    Transition::Reset(Box::new(PlayingScreen::resume(run, game)))
+                }
+                None => Transition::None,
+            },
+            Entry::Play => Transition::Push(Box::new(LevelSelectScreen::new())),
+            Entry::Records => {
+                self.show_stats = !self.show_stats;
+                Transition::None
+            }
+            Entry::Quit => Transition::Quit,
+        }
+    }
+
+    fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let layout = MenuLayout::new(fb, rl);
+        fb.clear_with(Color::BLACK);
+        layout.text(fb, "SCARY CLUB", 60, 40, 70, Color::WHITE);
+        layout.text(fb, "Ayuda al abuelo a encontrar la llave para entrar a su casa", 70, 130, 30, Color::WHITE);
+        layout.text(fb, "y evita al malvado en una noche obscura", 70, 160, 30, Color::WHITE);
+        draw_menu_image(fb, &game.tex, 'c', 0.5);
+
+        for (i, entry) in Self::entries(game).into_iter().enumerate() {
+            let col = if i == self.cursor { Color::YELLOW } else { Color::GRAY };
+            layout.text(fb, &Self::label(entry, game), 80, 720 + i as i32 * 32, 28, col);
+        }
+        layout.text(fb, "UP/DOWN y ENTER   S: récords   ESC para salir", 60, 860, 20, Color::DARKGRAY);
+
+        if self.show_stats {
+            let x = rl.get_screen_width() - 480;
+            // the hand-made levels and the endless mode
+            layout.panel(fb, x - 20, 220, 460, 60 + (ENDLESS_LEVEL as i32 + 1) * 90, Color::new(0, 0, 0, 220));
+            layout.text(fb, "RÉCORDS", x, 235, 30, Color::YELLOW);
+            for i in 0..=ENDLESS_LEVEL {
+                let def = level_def(i);
+                let y = 280 + i as i32 * 90;
+                layout.text(fb, def.name, x, y, 24, Color::WHITE);
+                match game.best_times.get(def.name) {
+                    Some(b) => {
+                        layout.text(fb, &format!("Mejor tiempo: {}   Completado: {}", format_time(b.best_time), b.completions), x, y + 28, 20, Color::LIGHTGRAY);
+                        layout.text(fb, &format!("Menor recorrido: {:.1} celdas   Menos visto: {}", b.shortest_distance / game.block_size as f32, b.fewest_spotted), x, y + 52, 20, Color::LIGHTGRAY);
+                    }
+                    None => layout.text(fb, "Sin completar", x, y + 28, 20, Color::GRAY),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use raylib::prelude::*;
+
+use crate::audio::Sounds;
+use crate::font::{draw_text, TextStyle};
+use crate::framebuffer::Framebuffer;
+use crate::hud::{self, Rect};
+use crate::minimap::MinimapOptions;
+use crate::save::SaveGame;
+use crate::settings::Settings;
+use crate::stats::BestTimes;
+use crate::textures::TextureManager;
+use crate::transitions::{Effect, ScreenTransition};
+
+mod menu;
+mod level_select;
+mod playing;
+mod pause;
+mod win;
+mod game_over;
+mod editor;
+
+pub use menu::MenuScreen;
+pub use level_select::LevelSelectScreen;
+pub use playing::PlayingScreen;
+pub use pause::PauseScreen;
+pub use win::WinScreen;
+pub use game_over::GameOverScreen;
+pub use editor::EditorScreen;
+
+// Each part of the game (menus, a level, the editor...) is a Screen. The manager keeps them in a
+// stack: the top one gets the input, overlays (pause) are drawn over the screens below them, and
+// switching screens plays the transition effect chosen in the settings.
+
+// what a screen asks the manager to do after its update
+pub enum Transition {
+    None,
+    Push(Box<dyn Screen>),
+    Pop,
+    Replace(Box<dyn Screen>),
+    Reset(Box<dyn Screen>), // drops the whole stack, e.g. going back to the main menu
+    Quit,
+}
+
+pub trait Screen {
+    fn on_enter(&mut self, _game: &mut Game, _rl: &mut RaylibHandle) {}
+    fn on_exit(&mut self, _game: &mut Game, _rl: &mut RaylibHandle) {}
+    fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, fb: &Framebuffer) -> Transition;
+    fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer);
+    // overlays keep the screen below visible and open/close without a transition
+    fn is_overlay(&self) -> bool { false }
+}
+
+// state shared by every screen
+pub struct Game<'a> {
+    pub settings: Settings,
+    pub block_size: usize,
+    pub render_threads: usize,
+    pub tex: TextureManager,
+    pub depth_buffer: Vec<f32>,
+    pub save_game: SaveGame,
+    pub best_times: BestTimes,
+    pub minimap: MinimapOptions,
+    pub sounds: Sounds<'a>,
+}
+
+pub struct ScreenManager {
+    stack: Vec<Box<dyn Screen>>,
+    transition: Option<ScreenTransition>,
+    quit: bool,
+}
+
+impl ScreenManager {
+    pub fn new(mut first: Box<dyn Screen>, game: &mut Game, rl: &mut RaylibHandle) -> Self {
+        first.on_enter(game, rl);
+        ScreenManager { stack: vec![first], transition: None, quit: false }
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit || self.stack.is_empty()
+    }
+
+    pub fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, fb: &Framebuffer) {
+        // no input while the effect plays
+        if let Some(tr) = self.transition.as_mut() {
+            tr.advance(rl.get_frame_time());
+            if tr.finished() { self.transition = None; }
+            return;
+        }
+        let Some(top) = self.stack.last_mut() else { return; };
+        let transition = top.update(game, rl, fb);
+        let instant = match &transition {
+            Transition::None => return,
+            Transition::Quit => true,
+            Transition::Push(s) => s.is_overlay(),
+            Transition::Pop => top.is_overlay(),
+            _ => false,
+        };
+        // the framebuffer still holds the last frame of the outgoing screen
+        let effect = game.settings.transition;
+        if !instant && effect != Effect::None && game.settings.transition_time > 0.0 {
+            self.transition = Some(ScreenTransition::start(effect, game.settings.transition_time, fb));
+        }
+        self.apply(transition, game, rl);
+    }
+
+    fn apply(&mut self, transition: Transition, game: &mut Game, rl: &mut RaylibHandle) {
+        match transition {
+            Transition::None => {}
+            Transition::Quit => self.quit = true,
+            Transition::Push(mut s) => {
+                s.on_enter(game, rl);
+                self.stack.push(s);
+            }
+            Transition::Pop => {
+                if let Some(mut s) = self.stack.pop() { s.on_exit(game, rl); }
+            }
+            Transition::Replace(mut s) => {
+                if let Some(mut old) = self.stack.pop() { old.on_exit(game, rl); }
+                s.on_enter(game, rl);
+                self.stack.push(s);
+            }
+            Transition::Reset(mut s) => {
+                self.clear(game, rl);
+                s.on_enter(game, rl);
+                self.stack.push(s);
+            }
+        }
+    }
+
+    // exits every screen, top first (also used when the window is closed)
+    pub fn clear(&mut self, game: &mut Game, rl: &mut RaylibHandle) {
+        while let Some(mut s) = self.stack.pop() { s.on_exit(game, rl); }
+    }
+
+    pub fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer) {
+        // start at the topmost screen that isn't an overlay
+        let first = self.stack.iter().rposition(|s| !s.is_overlay()).unwrap_or(0);
+        for s in self.stack[first..].iter_mut() {
+            s.draw(game, rl, fb);
+        }
+        if let Some(tr) = self.transition.as_ref() { tr.compose(fb); }
+    }
+}
+
+// The menus were laid out for the 1300x900 window, this scales those positions to the framebuffer
+pub struct MenuLayout {
+    k: f32,
+}
+
+impl MenuLayout {
+    pub fn new(fb: &Framebuffer, rl: &RaylibHandle) -> Self {
+        MenuLayout { k: fb.width as f32 / rl.get_screen_width().max(1) as f32 }
+    }
+
+    pub fn at(&self, v: i32) -> i32 {
+        (v as f32 * self.k) as i32
+    }
+
+    pub fn text(&self, fb: &mut Framebuffer, text: &str, x: i32, y: i32, size: i32, color: Color) {
+        draw_text(fb, text, self.at(x), self.at(y), &TextStyle::new(size as f32 * self.k, color));
+    }
+
+    pub fn panel(&self, fb: &mut Framebuffer, x: i32, y: i32, w: i32, h: i32, color: Color) {
+        hud::fill_rect(fb, Rect { x: self.at(x), y: self.at(y), w: self.at(w), h: self.at(h) }, color);
+    }
+}
+
+// full screen picture of the menus, `drop` moves it down as a fraction of its height
+pub fn draw_menu_image(fb: &mut Framebuffer, tex: &TextureManager, id: char, drop: f32) {
+    let (tw, th) = tex.get_size(id);
+    if tw <= 1 || th <= 1 { return; }
+    let scale = (fb.height as f32 * 0.5) / th as f32;
+    let w = (tw as f32 * scale) as i32;
+    let h = (th as f32 * scale) as i32;
+    let x = fb.width as i32 / 2 - w / 2;
+    let y = fb.height as i32 / 2 - (h as f32 * drop) as i32;
+    hud::blit(fb, tex, id, Rect { x, y, w, h });
+}
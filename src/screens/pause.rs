@@ -0,0 +1,34 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::hud::{self, Anchor, Hud};
+
+use super::{Game, MenuScreen, Screen, Transition};
+
+// Drawn over the level, which stays frozen underneath. P resumes, M goes back to the menu
+// (the run is saved and can be continued from there)
+pub struct PauseScreen;
+
+impl PauseScreen {
+    pub fn new() -> Self {
+        PauseScreen
+    }
+}
+
+impl Screen for PauseScreen {
+    fn update(&mut self, _game: &mut Game, rl: &mut RaylibHandle, _fb: &Framebuffer) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_P) { return Transition::Pop; }
+        if rl.is_key_pressed(KeyboardKey::KEY_M) { return Transition::Reset(Box::new(MenuScreen::new())); }
+        Transition::None
+    }
+
+    fn draw(&mut self, _game: &mut Game, _rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let full = hud::Rect { x: 0, y: 0, w: fb.width as i32, h: fb.height as i32 };
+        hud::fill_rect(fb, full, Color::new(0, 0, 0, 150));
+        let hud = Hud::new(fb);
+        hud.text(fb, Anchor::Center, (0.0, 0.0), 64.0, "PAUSA", Color::WHITE);
+        hud.text(fb, Anchor::Bottom, (0.0, 80.0), 24.0, "P: continuar   M: menú", Color::LIGHTGRAY);
+    }
+
+    fn is_overlay(&self) -> bool { true }
+}
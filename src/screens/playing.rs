@@ -0,0 +1,225 @@
+use raylib::prelude::*;
+
+use crate::audio::Emitter;
+use crate::automap::AutomapView;
+use crate::framebuffer::Framebuffer;
+use crate::hud::{Anchor, Hud};
+use crate::input::PlayerInput;
+use crate::levels::{GameState, level_def};
+use crate::minimap::render_minimap;
+use crate::player::{MAX_STAMINA, lerp_player};
+use crate::postfx::{PostChain, PostEffect};
+use crate::render::render_world;
+use crate::replay::{self, Replay};
+use crate::save::{SavedRun, SAVE_PATH};
+use crate::settings::Settings;
+use crate::sprites::draw_sprites;
+use crate::stats::{STATS_PATH, format_time};
+use crate::world::{World, MAX_HEALTH};
+
+use super::{Game, GameOverScreen, PauseScreen, Screen, Transition, WinScreen};
+
+// A level being played. The simulation runs at a fixed rate and the frames render an
+// interpolation between the last two ticks.
+pub struct PlayingScreen {
+    level: usize,
+    world: World,
+    state: GameState,
+    recorder: Option<Replay>,
+    accumulator: f32,
+    pending_mouse_dx: f32,
+    automap: AutomapView,
+    post: PostChain,
+    puffle_song: Emitter,
+    enemy_steps: Emitter,
+}
+
+impl PlayingScreen {
+    // new run of `level` with a fresh seed, recorded if --record was given
+    pub fn new_run(level: usize, game: &Game) -> Self {
+        let seed = replay::new_seed();
+        let mut world = World::load(level, game.block_size, seed);
+        world.lives = game.settings.lives;
+        let recorder = game.settings.record_path.as_ref().map(|_| {
+            Replay::new(level, seed, replay::layout_hash(&world), game.settings.tick_rate, world.lives)
+        });
+        Self::with_world(level, world, recorder, game)
+    }
+
+    pub fn resume(run: &SavedRun, game: &Game) -> Self {
+        Self::with_world(run.level, run.restore(game.block_size), None, game)
+    }
+
+    fn silence(&mut self, game: &mut Game) {
+        self.puffle_song.silence(&mut game.sounds.taylor);
+        self.enemy_steps.silence(&mut game.sounds.chaser);
+    }
+
+    fn with_world(level: usize, world: World, recorder: Option<Replay>, game: &Game) -> Self {
+        // the --lut grading goes on top of the level's own effects
+        let mut effects = level_def(level).post.to_vec();
+        if let Some(path) = &game.settings.lut_path {
+            effects.push(PostEffect::LutFile(path.clone()));
+        }
+        PlayingScreen {
+            level,
+            world,
+            state: GameState::Playing,
+            recorder,
+            accumulator: 0.0,
+            pending_mouse_dx: 0.0,
+            automap: AutomapView::new(),
+            post: PostChain::new(&effects),
+            puffle_song: Emitter::new(&['p'], 1.0, 400.0),
+            enemy_steps: Emitter::new(&['e', 'f'], 0.8, 640.0),
+        }
+    }
+}
+
+impl Screen for PlayingScreen {
+    fn on_enter(&mut self, _game: &mut Game, rl: &mut RaylibHandle) {
+        rl.disable_cursor();
+    }
+
+    // leaving in the middle of the level (menu from the pause, closing the window) keeps the run
+    fn on_exit(&mut self, game: &mut Game, _rl: &mut RaylibHandle) {
+        finish_recording(&mut self.recorder, &self.world, self.state, &game.settings);
+        if self.state == GameState::Playing {
+            game.save_game.run = Some(SavedRun::capture(&self.world, self.level));
+            game.save_game.store(SAVE_PATH);
+        }
+        self.silence(game);
+    }
+
+    fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, _fb: &Framebuffer) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            self.silence(game);
+            return Transition::Push(Box::new(PauseScreen::new()));
+        }
+
+        let dt = rl.get_frame_time();
+        self.automap.handle_input(rl, dt);
+        // F6 toggles the view cone, F7 the enemy/item icons
+        if rl.is_key_pressed(KeyboardKey::KEY_F6) { game.minimap.show_cone = !game.minimap.show_cone; }
+        if rl.is_key_pressed(KeyboardKey::KEY_F7) { game.minimap.show_entities = !game.minimap.show_entities; }
+
+        // run as many fixed ticks as the elapsed time allows (capped to avoid a spiral after a stall)
+        let tick_dt = 1.0 / game.settings.tick_rate;
+        self.accumulator += dt.min(0.25);
+        self.pending_mouse_dx += rl.get_mouse_delta().x;
+        let mut input = PlayerInput::read_keys(rl);
+        let spotted = self.world.stats.spotted;
+        let (health, lives) = (self.world.health, self.world.lives);
+        while self.accumulator >= tick_dt && self.state == GameState::Playing {
+            input.mouse_dx = self.pending_mouse_dx;
+            self.pending_mouse_dx = 0.0;
+            if let Some(rec) = self.recorder.as_mut() { rec.push(&input); }
+            self.state = self.world.step(&input, tick_dt);
+            self.accumulator -= tick_dt;
+        }
+        for event in self.world.sounds.drain(..) {
+            game.sounds.play(event, &self.world.player);
+        }
+        // a red flash when a chaser notices the player
+        if self.world.stats.spotted > spotted { self.post.flash(Color::new(180, 0, 0, 255), 0.8); }
+        // a stronger one when it hits; losing a life refills the health, that one fades from black
+        if self.world.lives < lives {
+            self.post.flash(Color::BLACK, 1.0);
+        } else if self.world.health < health {
+            self.post.flash(Color::new(255, 0, 0, 255), 1.0);
+        }
+        self.post.update(dt);
+
+        match self.state {
+            // a finished level unlocks the next one, losing throws the run away
+            GameState::Win => {
+                game.save_game.complete_level(self.level);
+                let stats = self.world.stats;
+                let new_record = game.best_times.record(level_def(self.level).name, &stats);
+                game.best_times.store(STATS_PATH);
+                game.save_game.store(SAVE_PATH);
+                return Transition::Replace(Box::new(WinScreen::new(self.level, stats, new_record)));
+            }
+            GameState::GameOver => {
+                game.save_game.run = None;
+                game.save_game.store(SAVE_PATH);
+                return Transition::Replace(Box::new(GameOverScreen::new(self.level)));
+            }
+            _ => {}
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F5) {
+            game.save_game.run = Some(SavedRun::capture(&self.world, self.level));
+            game.save_game.store(SAVE_PATH);
+        }
+
+        //the puffle sings and the enemies walk, both heard from where they are
+        let player = &self.world.player;
+        game.sounds.music.follow(player, &self.world.enemies, &self.world.maze, game.block_size);
+        self.puffle_song.update(&mut game.sounds.taylor, player, &self.world.enemies, dt);
+        self.enemy_steps.update(&mut game.sounds.chaser, player, &self.world.enemies, dt);
+        Transition::None
+    }
+
+    fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let tick_dt = 1.0 / game.settings.tick_rate;
+        let alpha = (self.accumulator / tick_dt).clamp(0.0, 1.0);
+        let w = &mut self.world;
+        let player = lerp_player(&w.prev_player, &w.player, alpha);
+        let block_size = game.block_size;
+        let fog = &level_def(self.level).fog;
+
+        fb.clear();
+
+        // the automap replaces the 3D view while it's open, the game keeps running underneath
+        if self.automap.open {
+            self.automap.draw(fb, &w.maze, &w.explored, &player, &w.enemies, block_size);
+            let hud = Hud::new(fb);
+            hud.text(fb, Anchor::TopLeft, (10.0, 10.0), 20.0, "Mapa - TAB cerrar, rueda/+- zoom, IJKL mover, C centrar, R rotar", Color::WHITE);
+            return;
+        }
+
+        render_world(fb, &w.maze, block_size, &player, &game.tex, &mut game.depth_buffer, fog, game.render_threads, Some(&mut w.explored));
+        let proj_plane = (fb.width as f32) / (2.0 * (player.fov * 0.5).tan());
+        draw_sprites(fb, &player, &mut w.enemies, &game.tex, &game.depth_buffer, proj_plane, block_size, fog, alpha);
+        self.post.apply(fb, game.render_threads, game.settings.postfx);
+
+        let hud = Hud::new(fb);
+        let cell_px = hud.px(16.0) as u32;
+        let max_cells = game.minimap.max_cells;
+        let view_cells = |n: usize| (n as u32).min(max_cells) * cell_px;
+        let mini_w = view_cells(w.maze.iter().map(|r| r.len()).max().unwrap_or(0));
+        let mini_h = view_cells(w.maze.len());
+        let mini = hud.place_px(Anchor::BottomLeft, (8.0, 8.0), (mini_w as i32, mini_h as i32));
+        render_minimap(fb, &w.maze, block_size, &player, &w.enemies, &game.depth_buffer, mini.x.max(0) as u32, mini.y.max(0) as u32, cell_px, &game.minimap);
+
+        //the key slot stays empty until the player picks it up
+        let key_slot = if w.has_key { Some('k') } else { None };
+        hud.icon_slots(fb, &game.tex, Anchor::TopRight, (10.0, 40.0), 56.0, &[key_slot]);
+        hud.text(fb, Anchor::Top, (0.0, 10.0), 24.0, &format_time(w.stats.elapsed), Color::WHITE);
+
+        //health bar, it blinks while the player can't be hurt
+        let blink = w.invulnerable > 0.0 && (w.invulnerable * 8.0) as i32 % 2 == 0;
+        let fill = if blink { Color::new(255, 255, 255, 220) } else { Color::new(200, 30, 30, 230) };
+        let bar = hud.bar(fb, Anchor::TopLeft, (10.0, 10.0), (220.0, 18.0), w.health / MAX_HEALTH, fill, Color::new(0, 0, 0, 140));
+        //stamina under it, grey while the player is out of breath
+        let stamina_fill = if w.player.winded { Color::new(120, 120, 120, 220) } else { Color::new(230, 200, 40, 230) };
+        hud.bar(fb, Anchor::TopLeft, (10.0, 32.0), (160.0, 8.0), w.player.stamina / MAX_STAMINA, stamina_fill, Color::new(0, 0, 0, 140));
+        if w.lives > 1 || game.settings.lives > 1 {
+            let lives = format!("x{}", w.lives);
+            hud.text(fb, Anchor::TopLeft, (20.0 + bar.w as f32 / hud.scale, 8.0), 20.0, &lives, Color::WHITE);
+        }
+        hud.text(fb, Anchor::TopRight, (10.0, 10.0), 20.0, &format!("FPS: {}", rl.get_fps()), Color::WHITE);
+    }
+}
+
+// writes the active replay (if any) with the final state of the run
+fn finish_recording(recorder: &mut Option<Replay>, world: &World, state: GameState, settings: &Settings) {
+    let (Some(mut rec), Some(base)) = (recorder.take(), settings.record_path.as_ref()) else { return; };
+    rec.end = Some(replay::summary(world, state, rec.ticks.len()));
+    // one file per level played, so the runs of a session don't overwrite each other
+    let path = replay::run_path(base, rec.level, rec.seed);
+    match rec.save(&path) {
+        Ok(()) => println!("Replay guardado en {path}"),
+        Err(e) => eprintln!("No se pudo guardar el replay {path}: {e}"),
+    }
+}
@@ -0,0 +1,53 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::levels::{LEVELS, ENDLESS_LEVEL, level_def};
+use crate::stats::{RunStats, format_time};
+
+use super::{Game, MenuLayout, MenuScreen, PlayingScreen, Screen, Transition, draw_menu_image};
+
+// Level completed: stats of the run and its personal best
+pub struct WinScreen {
+    level: usize,
+    stats: RunStats,
+    new_record: bool,
+}
+
+impl WinScreen {
+    pub fn new(level: usize, stats: RunStats, new_record: bool) -> Self {
+        WinScreen { level, stats, new_record }
+    }
+}
+
+impl Screen for WinScreen {
+    fn update(&mut self, game: &mut Game, rl: &mut RaylibHandle, _fb: &Framebuffer) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            // the endless mode keeps generating new mazes
+            let next = if self.level == ENDLESS_LEVEL { ENDLESS_LEVEL } else { (self.level + 1) % LEVELS.len() };
+            return Transition::Replace(Box::new(PlayingScreen::new_run(next, game)));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            return Transition::Reset(Box::new(MenuScreen::new()));
+        }
+        Transition::None
+    }
+
+    fn draw(&mut self, game: &mut Game, rl: &RaylibHandle, fb: &mut Framebuffer) {
+        let layout = MenuLayout::new(fb, rl);
+        fb.clear_with(Color::WHITE);
+        draw_menu_image(fb, &game.tex, 'w', 0.25);
+        layout.text(fb, "NIVEL COMPLETADO", 350, 100, 60, Color::GREEN);
+        layout.text(fb, "ENTER: siguiente nivel", 500, 200, 30, Color::BLACK);
+        layout.text(fb, "M: menú", 500, 240, 30, Color::BLACK);
+
+        // how the level went
+        let run = &self.stats;
+        let best = game.best_times.get(level_def(self.level).name).map(|b| b.best_time).unwrap_or(run.elapsed);
+        layout.text(fb, &format!("Tiempo: {}", format_time(run.elapsed)), 60, 300, 26, Color::DARKGRAY);
+        layout.text(fb, &format!("Mejor: {}", format_time(best)), 60, 332, 26, Color::DARKGRAY);
+        layout.text(fb, &format!("Recorrido: {:.1} celdas", run.distance / game.block_size as f32), 60, 364, 26, Color::DARKGRAY);
+        layout.text(fb, &format!("Te vieron: {} veces", run.spotted), 60, 396, 26, Color::DARKGRAY);
+        layout.text(fb, &format!("Objetos: {}", run.items), 60, 428, 26, Color::DARKGRAY);
+        if self.new_record { layout.text(fb, "¡Nuevo récord!", 60, 470, 30, Color::ORANGE); }
+    }
+}
@@ -0,0 +1,115 @@
+use raylib::prelude::*;
+
+use crate::transitions::Effect;
+
+// Internal render resolutions that can be cycled in game (fraction of the window size)
+pub const RENDER_SCALES: [f32; 3] = [0.5, 0.75, 1.0];
+
+// Options read from the command line, e.g. `cargo run -- --scale=75 --smooth --fps=144`
+// or `cargo run -- --replay=bug.replay` to check a recorded run without opening a window.
+// `--transition=melt --transition-time=1.2` picks the effect used between screens,
+// `--no-audio` runs without opening the audio device (headless machines),
+// `--lives=3` gives the runs extra lives (one by default),
+// `--lut=grade.png` adds a colour grading LUT strip after the effects of every level
+pub struct Settings {
+    pub render_scale: f32,    // internal resolution relative to the window
+    pub smooth_upscale: bool, // linear filtering when upscaling, nearest otherwise
+    pub tick_rate: f32,       // simulation ticks per second
+    pub frame_cap: u32,       // max rendered frames per second, 0 = unlimited
+    pub vsync: bool,
+    pub record_path: Option<String>, // write a replay of every played level, suffixed with level and seed
+    pub replay_path: Option<String>, // play this replay headless and exit
+    pub transition: Effect,          // effect between screens
+    pub transition_time: f32,        // seconds
+    pub postfx: bool,                // post effects of the levels (vignette, grain...), F8 toggles
+    pub audio: bool,
+    pub lives: u32,                  // lives of a new run
+    pub lut_path: Option<String>,    // extra LUT applied on every level
+}
+
+impl Settings {
+    pub fn from_args() -> Self {
+        let mut settings = Settings {
+            render_scale: 1.0,
+            smooth_upscale: false,
+            tick_rate: 60.0,
+            frame_cap: 60,
+            vsync: false,
+            record_path: None,
+            replay_path: None,
+            transition: Effect::Fade,
+            transition_time: 0.5,
+            postfx: true,
+            audio: true,
+            lives: 1,
+            lut_path: None,
+        };
+        for arg in std::env::args().skip(1) {
+            if let Some(value) = arg.strip_prefix("--scale=") {
+                match value.trim_end_matches('%').parse::<f32>() {
+                    // accepts 0.5 or 50
+                    Ok(v) => settings.render_scale = (if v > 1.0 { v / 100.0 } else { v }).clamp(0.1, 1.0),
+                    Err(_) => eprintln!("Escala inválida: {value}"),
+                }
+            } else if let Some(value) = arg.strip_prefix("--fps=") {
+                match value.parse::<u32>() {
+                    Ok(v) => settings.frame_cap = v,
+                    Err(_) => eprintln!("Límite de FPS inválido: {value}"),
+                }
+            } else if let Some(value) = arg.strip_prefix("--tick=") {
+                match value.parse::<f32>() {
+                    Ok(v) if v >= 1.0 => settings.tick_rate = v,
+                    _ => eprintln!("Frecuencia de simulación inválida: {value}"),
+                }
+            } else if let Some(value) = arg.strip_prefix("--record=") {
+                settings.record_path = Some(value.to_string());
+            } else if let Some(value) = arg.strip_prefix("--replay=") {
+                settings.replay_path = Some(value.to_string());
+            } else if let Some(value) = arg.strip_prefix("--transition=") {
+                match Effect::parse(value) {
+                    Some(effect) => settings.transition = effect,
+                    None => eprintln!("Transición inválida: {value} (none, fade, wipe o melt)"),
+                }
+            } else if let Some(value) = arg.strip_prefix("--transition-time=") {
+                match value.parse::<f32>() {
+                    Ok(v) if v >= 0.0 => settings.transition_time = v,
+                    _ => eprintln!("Duración de transición inválida: {value}"),
+                }
+            } else if let Some(value) = arg.strip_prefix("--lives=") {
+                match value.parse::<u32>() {
+                    Ok(v) if v >= 1 => settings.lives = v,
+                    _ => eprintln!("Número de vidas inválido: {value}"),
+                }
+            } else if let Some(value) = arg.strip_prefix("--lut=") {
+                settings.lut_path = Some(value.to_string());
+            } else if arg == "--smooth" {
+                settings.smooth_upscale = true;
+            } else if arg == "--vsync" {
+                settings.vsync = true;
+            } else if arg == "--no-postfx" {
+                settings.postfx = false;
+            } else if arg == "--no-audio" {
+                settings.audio = false;
+            }
+        }
+        settings
+    }
+
+    // framebuffer size for a given window size
+    pub fn render_size(&self, window_w: i32, window_h: i32) -> (u32, u32) {
+        let w = (window_w as f32 * self.render_scale).round().max(1.0) as u32;
+        let h = (window_h as f32 * self.render_scale).round().max(1.0) as u32;
+        (w, h)
+    }
+
+    pub fn cycle_render_scale(&mut self) {
+        self.render_scale = RENDER_SCALES.iter()
+            .copied()
+            .find(|s| *s > self.render_scale + 0.01)
+            .unwrap_or(RENDER_SCALES[0]);
+    }
+
+    pub fn upscale_filter(&self) -> TextureFilter {
+        if self.smooth_upscale { TextureFilter::TEXTURE_FILTER_BILINEAR } else { TextureFilter::TEXTURE_FILTER_POINT }
+    }
+}
@@ -4,27 +4,43 @@ use std::f32::consts::PI;
 use crate::player::Player;
 use crate::framebuffer::Framebuffer;
 use crate::textures::TextureManager;
+use crate::fog::Fog;
 
 pub const TRANSPARENT_COLOR: Color = Color::new(152, 0, 136, 255);
 // Factor base para reducir la altura original (comparada con un bloque de muro)
 pub const ENEMY_BASE_SCALE: f32 = 0.5;
 
+#[derive(Clone)]
 pub struct Enemy {
     pub pos: Vector2,
+    pub prev_pos: Vector2, // posición en el tick anterior, para interpolar al dibujar
     pub id: char,
     pub scale: f32, // factor adicional encima de ENEMY_BASE_SCALE
+    pub alert: bool, // el enemigo está persiguiendo al jugador
+    pub cooldown: f32, // segundos hasta que puede volver a atacar
 }
 
 impl Enemy {
     pub fn new(x: f32, y: f32, id: char) -> Self {
         Self {
             pos: Vector2::new(x, y),
+            prev_pos: Vector2::new(x, y),
             id,
             scale: 1.0,
+            alert: false,
+            cooldown: 0.0,
         }
     }
     pub fn with_scale(x: f32, y: f32, id: char, scale: f32) -> Self {
-        Self { pos: Vector2::new(x, y), id, scale }
+        Self { pos: Vector2::new(x, y), prev_pos: Vector2::new(x, y), id, scale, alert: false, cooldown: 0.0 }
+    }
+
+    // Posición entre el tick anterior y el actual (alpha en 0..1)
+    pub fn draw_pos(&self, alpha: f32) -> Vector2 {
+        Vector2::new(
+            self.prev_pos.x + (self.pos.x - self.prev_pos.x) * alpha,
+            self.prev_pos.y + (self.pos.y - self.prev_pos.y) * alpha,
+        )
     }
 }
 
@@ -36,6 +52,8 @@ pub fn draw_sprites(
     depth_buffer: &[f32],
     proj_plane: f32,
     block_size: usize,
+    fog: &Fog,
+    alpha: f32,
 ) {
     if enemies.is_empty() {
         return;
@@ -45,8 +63,9 @@ pub fn draw_sprites(
     // Ordenar por distancia (más lejanos primero para painter's algorithm)
     // Se revierte al cálculo manual de la distancia al cuadrado, ya que `length_sq()` no existe.
     enemies.sort_by(|a, b| {
-        let da = (a.pos.x - player.pos.x).powi(2) + (a.pos.y - player.pos.y).powi(2);
-        let db = (b.pos.x - player.pos.x).powi(2) + (b.pos.y - player.pos.y).powi(2);
+        let (pa, pb) = (a.draw_pos(alpha), b.draw_pos(alpha));
+        let da = (pa.x - player.pos.x).powi(2) + (pa.y - player.pos.y).powi(2);
+        let db = (pb.x - player.pos.x).powi(2) + (pb.y - player.pos.y).powi(2);
         db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
     });
 
@@ -58,8 +77,9 @@ pub fn draw_sprites(
 
     for enemy in enemies.iter() {
         // 1. Calcular ángulo y distancia al sprite
-        let dx = enemy.pos.x - player.pos.x;
-        let dy = enemy.pos.y - player.pos.y;
+        let pos = enemy.draw_pos(alpha);
+        let dx = pos.x - player.pos.x;
+        let dy = pos.y - player.pos.y;
         let dist = (dx * dx + dy * dy).sqrt();
 
         let sprite_angle = dy.atan2(dx);
@@ -90,7 +110,9 @@ pub fn draw_sprites(
         let sprite_screen_x = half_screen_w + (angle_diff / half_fov) * half_screen_w;
 
         // 6. Calcular los límites de dibujo (start/end para X/Y)
-        let v_move_screen = sprite_height * 0.5; 
+        // apoyado en el suelo, que sube en pantalla cuando la cámara baja (agachado)
+        let eye_shift = (player.eye() - 0.5) * block_size as f32 * proj_plane / dist_corrected;
+        let v_move_screen = sprite_height * 0.5 + eye_shift;
         
         let draw_start_y = (half_screen_h - sprite_height * 0.5 + v_move_screen).max(0.0) as i32;
         let draw_end_y = (half_screen_h + sprite_height * 0.5 + v_move_screen).min(screen_h as f32) as i32;
@@ -124,6 +146,8 @@ pub fn draw_sprites(
             }
         }
 
+        let fog_amount = fog.factor(dist_corrected);
+
         // 7. Dibujar las columnas verticales del sprite (stripes)
         for stripe in draw_start_x..draw_end_x {
             let stripe_idx = stripe as usize;
@@ -147,16 +171,12 @@ pub fn draw_sprites(
 
                 if color == TRANSPARENT_COLOR || color.a == 0 { continue; }
 
-                // Sombreado por distancia
-                let shade = (1.0 / (1.0 + dist_corrected * 0.1)).clamp(0.3, 1.0);
-                let final_color = Color {
-                    r: (color.r as f32 * shade) as u8,
-                    g: (color.g as f32 * shade) as u8,
-                    b: (color.b as f32 * shade) as u8,
-                    a: 255,
-                };
+                // Niebla por distancia (misma que paredes y suelo)
+                let final_color = fog.mix(color, fog_amount);
 
-                framebuffer.set_pixel_color(stripe as u32, y as u32, final_color);
+                // Los pixeles semitransparentes (fantasmas, humo, bordes suaves) se mezclan
+                // con lo ya dibujado; como se pinta de lejos a cerca el resultado es correcto.
+                framebuffer.blend_pixel_color(stripe as u32, y as u32, final_color);
             }
         }
     }
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+// Statistics of the current run, updated by the simulation every tick
+#[derive(Copy, Clone, Default)]
+pub struct RunStats {
+    pub elapsed: f32,  // seconds
+    pub distance: f32, // world units walked
+    pub spotted: u32,  // times an enemy started chasing the player
+    pub items: u32,    // items picked up
+}
+
+// Personal bests of one level
+#[derive(Copy, Clone)]
+pub struct PersonalBest {
+    pub best_time: f32,
+    pub shortest_distance: f32,
+    pub fewest_spotted: u32,
+    pub completions: u32,
+}
+
+// Personal bests by `LevelDef.name`, stored in a text file with one line per level:
+//   <best_time> <shortest_distance> <fewest_spotted> <completions> <level name>
+pub const STATS_PATH: &str = "stats.txt";
+
+pub struct BestTimes {
+    pub levels: HashMap<String, PersonalBest>,
+}
+
+impl BestTimes {
+    pub fn load_or_new(path: &str) -> Self {
+        let mut levels = HashMap::new();
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                for line in text.lines() {
+                    let mut parts = line.splitn(5, ' ');
+                    let parsed = (|| {
+                        let best_time = parts.next()?.parse().ok()?;
+                        let shortest_distance = parts.next()?.parse().ok()?;
+                        let fewest_spotted = parts.next()?.parse().ok()?;
+                        let completions = parts.next()?.parse().ok()?;
+                        let name = parts.next()?.to_string();
+                        Some((name, PersonalBest { best_time, shortest_distance, fewest_spotted, completions }))
+                    })();
+                    match parsed {
+                        Some((name, best)) => { levels.insert(name, best); }
+                        None => eprintln!("Línea de récords inválida: {line}"),
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("No se pudieron leer los récords {path}: {e}"),
+        }
+        BestTimes { levels }
+    }
+
+    pub fn get(&self, level_name: &str) -> Option<&PersonalBest> {
+        self.levels.get(level_name)
+    }
+
+    // Records a completed run, returns true if it is a new best time
+    pub fn record(&mut self, level_name: &str, run: &RunStats) -> bool {
+        match self.levels.get_mut(level_name) {
+            Some(best) => {
+                let new_time = run.elapsed < best.best_time;
+                best.best_time = best.best_time.min(run.elapsed);
+                best.shortest_distance = best.shortest_distance.min(run.distance);
+                best.fewest_spotted = best.fewest_spotted.min(run.spotted);
+                best.completions += 1;
+                new_time
+            }
+            None => {
+                self.levels.insert(level_name.to_string(), PersonalBest {
+                    best_time: run.elapsed,
+                    shortest_distance: run.distance,
+                    fewest_spotted: run.spotted,
+                    completions: 1,
+                });
+                true
+            }
+        }
+    }
+
+    pub fn store(&self, path: &str) {
+        let mut names: Vec<&String> = self.levels.keys().collect();
+        names.sort();
+        let mut out = String::new();
+        for name in names {
+            let b = &self.levels[name];
+            out.push_str(&format!("{} {} {} {} {}\n", b.best_time, b.shortest_distance, b.fewest_spotted, b.completions, name));
+        }
+        if let Err(e) = fs::write(path, out) {
+            eprintln!("No se pudieron guardar los récords en {path}: {e}");
+        }
+    }
+}
+
+// 83.456 -> "1:23.46", rounded to hundredths before splitting so 59.999 shows "1:00.00"
+pub fn format_time(secs: f32) -> String {
+    let cs = (secs.max(0.0) * 100.0).round() as u32;
+    let (minutes, rest) = (cs / 6000, cs % 6000);
+    format!("{minutes}:{:02}.{:02}", rest / 100, rest % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_time_rounds_before_splitting() {
+        assert_eq!(format_time(0.0), "0:00.00");
+        assert_eq!(format_time(83.456), "1:23.46");
+        assert_eq!(format_time(59.999), "1:00.00");
+        assert_eq!(format_time(119.996), "2:00.00");
+        assert_eq!(format_time(9.5), "0:09.50");
+        assert_eq!(format_time(-3.0), "0:00.00");
+    }
+}
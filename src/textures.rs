@@ -1,5 +1,6 @@
 use raylib::prelude::*;
 use std::collections::HashMap;
+use std::fmt;
 
 pub struct CpuTexture {
     pub w: u32,
@@ -7,6 +8,30 @@ pub struct CpuTexture {
     pub pixels: Vec<u8>, // RGBA8
 }
 
+/// Errores al convertir una imagen a textura de CPU.
+#[derive(Debug)]
+pub enum TextureError {
+    Load(String),                          // raylib no pudo abrir/decodificar el archivo
+    Empty,                                 // imagen de 0x0
+    NoData,                                // imagen sin datos de pixeles
+    Compressed(PixelFormat),               // formato comprimido de GPU que raylib no descomprime
+    Unsupported(PixelFormat),              // raylib no pudo convertir a RGBA8
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Load(reason) => write!(f, "No se pudo leer la imagen: {reason}"),
+            TextureError::Empty => write!(f, "Imagen vacía"),
+            TextureError::NoData => write!(f, "Imagen sin datos"),
+            TextureError::Compressed(fmt) => write!(f, "Formato comprimido no convertible ({fmt:?})"),
+            TextureError::Unsupported(fmt) => write!(f, "Formato no soportado ({fmt:?})"),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
 /// Maneja texturas en CPU (formato normalizado RGBA8) para muestreo seguro.
 pub struct TextureManager {
     tex: HashMap<char, CpuTexture>,   // paredes
@@ -38,22 +63,22 @@ impl TextureManager {
     }
 
     fn load_sky(&mut self, path: &str) {
-        if let Ok(img) = Image::load_image(path) {
-            if let Some(ct) = Self::to_cpu_texture(img) { self.sky = Some(ct); }
-        } else { eprintln!("No se pudo cargar cielo {path}, se usará gradiente"); }
+        match Self::load_cpu_texture(path) {
+            Ok(ct) => self.sky = Some(ct),
+            Err(e) => eprintln!("No se pudo cargar cielo {path}: {e}, se usará gradiente"),
+        }
     }
 
     fn load_ground(&mut self, path: &str) {
-        if let Ok(img) = Image::load_image(path) {
-            if let Some(ct) = Self::to_cpu_texture(img) { self.ground = Some(ct); }
-        } else { eprintln!("No se pudo cargar suelo {path}, se usará color"); }
+        match Self::load_cpu_texture(path) {
+            Ok(ct) => self.ground = Some(ct),
+            Err(e) => eprintln!("No se pudo cargar suelo {path}: {e}, se usará color"),
+        }
     }
 
     fn load_one(&mut self, ch: char, path: &str) {
-        match Image::load_image(path) {
-            Ok(img) => {
-                if let Some(ct) = Self::to_cpu_texture(img) { self.tex.insert(ch, ct); } else { self.insert_dummy(ch); }
-            }
+        match Self::load_cpu_texture(path) {
+            Ok(ct) => { self.tex.insert(ch, ct); }
             Err(e) => {
                 eprintln!("No se pudo cargar {path}: {e} (dummy)");
                 self.insert_dummy(ch);
@@ -61,40 +86,45 @@ impl TextureManager {
         }
     }
 
-    fn to_cpu_texture(img: Image) -> Option<CpuTexture> {
-        // Rechazar formatos comprimidos para simplicidad
+    /// Carga una imagen de disco y la normaliza a RGBA8.
+    pub fn load_cpu_texture(path: &str) -> Result<CpuTexture, TextureError> {
+        let img = Image::load_image(path)
+            .map_err(|e| TextureError::Load(e.to_string()))?;
+        Self::to_cpu_texture(img)
+    }
+
+    /// Convierte cualquier formato que raylib sepa leer (8/16/32 bits, float, gris, alfa)
+    /// a RGBA8 conservando el canal alfa. Las imágenes con paleta (PNG/BMP/TGA indexados) ya
+    /// llegan expandidas a RGB/RGBA desde el decodificador. Los formatos comprimidos de GPU
+    /// (DXT, ETC, PVRT, ASTC) también pasan por `ImageFormat`; si la build de raylib no sabe
+    /// descomprimirlos la imagen sigue comprimida y se reporta como error.
+    pub fn to_cpu_texture(mut img: Image) -> Result<CpuTexture, TextureError> {
         use PixelFormat::*;
+        if img.width <= 0 || img.height <= 0 {
+            return Err(TextureError::Empty);
+        }
+        if img.data.is_null() {
+            return Err(TextureError::NoData);
+        }
         let fmt = img.format();
-        let (w, h) = (img.width.max(1) as u32, img.height.max(1) as u32);
-        let mut rgba: Vec<u8> = Vec::with_capacity((w * h * 4) as usize);
-        unsafe {
-            let ptr = img.data as *const u8;
-            if ptr.is_null() { return None; }
-            let src_slice = std::slice::from_raw_parts(ptr, (w * h * Self::bpp(fmt) as u32) as usize);
-            match fmt {
-                PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 => { rgba.extend_from_slice(src_slice); }
-                PIXELFORMAT_UNCOMPRESSED_R8G8B8 => {
-                    for px in src_slice.chunks_exact(3) { rgba.extend_from_slice(&[px[0], px[1], px[2], 255]); }
-                }
-                PIXELFORMAT_UNCOMPRESSED_GRAYSCALE => { for g in src_slice { rgba.extend_from_slice(&[*g, *g, *g, 255]); } }
-                PIXELFORMAT_UNCOMPRESSED_GRAY_ALPHA => { for ga in src_slice.chunks_exact(2) { rgba.extend_from_slice(&[ga[0], ga[0], ga[0], ga[1]]); } }
-                _ => { eprintln!("Formato no soportado ({fmt:?}) se usa dummy"); return None; }
+        if fmt != PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 {
+            // ImageFormat de raylib hace la conversión (16 bits, float, half float y comprimidos
+            // cuando puede decodificarlos)
+            img.set_format(PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+            if img.format() != PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 || img.data.is_null() {
+                if Self::is_compressed(fmt) { return Err(TextureError::Compressed(fmt)); }
+                return Err(TextureError::Unsupported(fmt));
             }
         }
-        if rgba.len() != (w * h * 4) as usize { return None; }
-        Some(CpuTexture { w, h, pixels: rgba })
+        let (w, h) = (img.width as u32, img.height as u32);
+        let len = w as usize * h as usize * 4;
+        let pixels = unsafe { std::slice::from_raw_parts(img.data as *const u8, len).to_vec() };
+        Ok(CpuTexture { w, h, pixels })
     }
 
     #[inline]
-    fn bpp(fmt: PixelFormat) -> usize {
-        use PixelFormat::*;
-        match fmt {
-            PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 => 4,
-            PIXELFORMAT_UNCOMPRESSED_R8G8B8 => 3,
-            PIXELFORMAT_UNCOMPRESSED_GRAY_ALPHA => 2,
-            PIXELFORMAT_UNCOMPRESSED_GRAYSCALE => 1,
-            _ => 4,
-        }
+    fn is_compressed(fmt: PixelFormat) -> bool {
+        (fmt as i32) >= PixelFormat::PIXELFORMAT_COMPRESSED_DXT1_RGB as i32
     }
 
     fn insert_dummy(&mut self, ch: char) {
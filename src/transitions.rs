@@ -0,0 +1,120 @@
+use raylib::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::mazegen::Rng;
+
+// Effects used when switching screens. They are composed in the framebuffer from a copy of the
+// last frame of the outgoing screen and the frames drawn by the incoming one.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Effect {
+    None,
+    Fade, // to black and back
+    Wipe, // the new screen slides in from the left edge
+    Melt, // columns of the old screen drip down at different speeds
+}
+
+impl Effect {
+    pub fn parse(name: &str) -> Option<Effect> {
+        match name {
+            "none" => Some(Effect::None),
+            "fade" => Some(Effect::Fade),
+            "wipe" => Some(Effect::Wipe),
+            "melt" => Some(Effect::Melt),
+            _ => None,
+        }
+    }
+}
+
+// columns of the melt move in groups of this many pixels
+const MELT_COLUMN: usize = 4;
+// the last column starts at most this fraction of the duration later than the first
+const MELT_MAX_DELAY: f32 = 0.35;
+
+pub struct ScreenTransition {
+    effect: Effect,
+    duration: f32,
+    t: f32,
+    from: Vec<Color>, // last frame of the outgoing screen
+    width: u32,
+    height: u32,
+    delays: Vec<f32>, // melt start of each column group, 0..MELT_MAX_DELAY
+}
+
+impl ScreenTransition {
+    pub fn start(effect: Effect, duration: f32, fb: &Framebuffer) -> Self {
+        let groups = (fb.width as usize).div_ceil(MELT_COLUMN);
+        let mut delays = Vec::with_capacity(groups);
+        if effect == Effect::Melt {
+            // random walk so neighbour columns start close to each other, like the classic effect
+            let mut rng = Rng::new(fb.width as u64 * 31 + fb.height as u64);
+            let mut d = rng.below(100) as f32 / 100.0 * MELT_MAX_DELAY;
+            for _ in 0..groups {
+                let step = (rng.below(3) as f32 - 1.0) * MELT_MAX_DELAY * 0.08;
+                d = (d + step).clamp(0.0, MELT_MAX_DELAY);
+                delays.push(d);
+            }
+        }
+        ScreenTransition {
+            effect,
+            duration: duration.max(0.001),
+            t: 0.0,
+            from: fb.pixels().to_vec(),
+            width: fb.width,
+            height: fb.height,
+            delays,
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.t += dt;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.effect == Effect::None || self.t >= self.duration
+    }
+
+    // progress in 0..=1
+    fn progress(&self) -> f32 {
+        (self.t / self.duration).clamp(0.0, 1.0)
+    }
+
+    // `fb` holds the frame of the incoming screen; the outgoing frame is mixed over it
+    pub fn compose(&self, fb: &mut Framebuffer) {
+        // a resize in the middle drops the old frame
+        if fb.width != self.width || fb.height != self.height { return; }
+        let p = self.progress();
+        let (w, h) = (self.width as usize, self.height as usize);
+        let from = &self.from;
+        let pixels = fb.pixels_mut();
+        match self.effect {
+            Effect::None => {}
+            Effect::Fade => {
+                // first half darkens the old frame, second half brings up the new one
+                let (src, k): (Option<&[Color]>, f32) = if p < 0.5 { (Some(from), 1.0 - 2.0 * p) } else { (None, 2.0 * p - 1.0) };
+                for (i, out) in pixels.iter_mut().enumerate() {
+                    let c = src.map_or(*out, |s| s[i]);
+                    *out = Color::new((c.r as f32 * k) as u8, (c.g as f32 * k) as u8, (c.b as f32 * k) as u8, 255);
+                }
+            }
+            Effect::Wipe => {
+                let edge = (p * w as f32) as usize;
+                for y in 0..h {
+                    let row = y * w;
+                    pixels[row + edge.min(w)..row + w].copy_from_slice(&from[row + edge.min(w)..row + w]);
+                }
+            }
+            Effect::Melt => {
+                for x in 0..w {
+                    let delay = self.delays[x / MELT_COLUMN];
+                    let k = ((p - delay) / (1.0 - MELT_MAX_DELAY)).clamp(0.0, 1.0);
+                    // accelerate a bit at the start, like something dripping
+                    let shift = (k * k * h as f32) as usize;
+                    for y in shift..h {
+                        pixels[y * w + x] = from[(y - shift) * w + x];
+                    }
+                }
+            }
+        }
+    }
+}
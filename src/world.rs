@@ -0,0 +1,242 @@
+use raylib::prelude::*;
+use crate::audio::SoundEvent;
+
+use crate::maze::Maze;
+use crate::player::{Player, WALK_SPEED, process_events};
+use crate::sprites::Enemy;
+use crate::levels::{GameState, Surface, level_def, load_level_index};
+use crate::input::PlayerInput;
+use crate::stats::RunStats;
+use crate::automap::{Explored, new_explored};
+use crate::physics;
+
+// Everything the simulation needs for one level. It is advanced in fixed ticks by `step`,
+// independent from the frame rate.
+
+pub struct World {
+    pub maze: Maze,
+    pub enemies: Vec<Enemy>,
+    pub player: Player,
+    pub prev_player: Player, // player on the previous tick, for render interpolation
+    pub has_key: bool,
+    pub block_size: usize,
+    pub seed: u64, // seed of the run, recorded in replays
+    pub stats: RunStats,
+    pub explored: Explored, // cells seen so far, shown on the automap
+    pub floor: Surface,
+    pub sounds: Vec<SoundEvent>, // emitted by the ticks, drained by whoever plays them
+    stride: f32,                 // distance walked since the last footstep
+    at_door: bool,               // touching the iglo, so the locked door is heard once
+    pub health: f32,             // 0..MAX_HEALTH, losing it all costs a life
+    pub lives: u32,              // the run ends when the last one is lost
+    pub invulnerable: f32,       // seconds left without taking damage after a hit
+    spawn: Player,               // where the player comes back after losing a life
+}
+
+// distance between two footsteps, in blocks
+const STRIDE: f32 = 0.6;
+
+// Damage tuning: a chaser touching the player hurts CONTACT_DAMAGE and then waits
+// ATTACK_COOLDOWN before it can hit again, the player can't be hurt for INVULNERABILITY seconds
+// after any hit (a bit longer after losing a life)
+pub const MAX_HEALTH: f32 = 100.0;
+const CONTACT_DAMAGE: f32 = 34.0;
+const ATTACK_COOLDOWN: f32 = 1.0;
+const INVULNERABILITY: f32 = 1.5;
+const RESPAWN_INVULNERABILITY: f32 = 3.0;
+
+impl World {
+    // `level` is an index in LEVELS or ENDLESS_LEVEL
+    pub fn load(level: usize, block_size: usize, seed: u64) -> Self {
+        let (maze, enemies, start) = load_level_index(level, block_size, seed);
+        Self::from_parts(maze, enemies, start, block_size, seed, level_def(level).floor)
+    }
+
+    // `start` is (x, y, angle) in blocks
+    pub fn from_parts(maze: Maze, enemies: Vec<Enemy>, start: (f32, f32, f32), block_size: usize, seed: u64, floor: Surface) -> Self {
+        let player = Player::new(Vector2::new(start.0 * block_size as f32, start.1 * block_size as f32), start.2);
+        let explored = new_explored(&maze);
+        World {
+            maze, enemies, player, prev_player: player, has_key: false, block_size, seed,
+            stats: RunStats::default(), explored, floor, sounds: Vec::new(), stride: 0.0, at_door: false,
+            health: MAX_HEALTH, lives: 1, invulnerable: 0.0, spawn: player,
+        }
+    }
+
+    // Advance the game by one tick of `dt` seconds, returns the resulting game state
+    pub fn step(&mut self, input: &PlayerInput, dt: f32) -> GameState {
+        let block_size = self.block_size;
+        self.prev_player = self.player;
+        for e in &mut self.enemies { e.prev_pos = e.pos; }
+
+        process_events(&mut self.player, input, dt, &self.maze, block_size);
+        let player = self.player;
+        self.stats.elapsed += dt;
+        let (mx, my) = (player.pos.x - self.prev_player.pos.x, player.pos.y - self.prev_player.pos.y);
+        let walked = (mx * mx + my * my).sqrt();
+        self.stats.distance += walked;
+
+        // one footstep every STRIDE actually walked, pushing against a wall makes no sound
+        self.stride += walked;
+        if self.stride >= STRIDE * block_size as f32 {
+            self.stride = 0.0;
+            // crouched steps are soft, sprinting ones loud
+            let loudness = if player.is_crouching() { 0.35 } else { (player.speed() / WALK_SPEED).clamp(0.6, 1.4) };
+            self.sounds.push(SoundEvent::Footstep(self.floor, loudness));
+        }
+
+        // The bad guy hears the player within ENEMY_HEARING blocks plus the noise of the movement
+        // (standing still, crouching or sprinting change it) or sees it within the chase radius
+        // when no wall is in between, and then follows in that radius
+        const ENEMY_CHASE_SPEED: f32 = 0.5;
+        const ENEMY_CHASE_RADIUS: f32 = 6.0;
+        const ENEMY_HEARING: f32 = 2.5;
+        const ENEMY_STOP_DIST: f32 = 0.15;
+        let chase_speed = ENEMY_CHASE_SPEED * block_size as f32;
+        let activation_dist_sq = (ENEMY_CHASE_RADIUS * block_size as f32).powi(2);
+        let hearing_dist_sq = ((ENEMY_HEARING + player.noise) * block_size as f32).powi(2);
+        let stop_dist_sq = (ENEMY_STOP_DIST * block_size as f32).powi(2);
+        for f in &mut self.enemies {
+            if f.id != 'f' { continue; }
+            let dx = player.pos.x - f.pos.x;
+            let dy = player.pos.y - f.pos.y;
+            let dist_sq = dx*dx + dy*dy;
+            let in_range = dist_sq <= hearing_dist_sq
+                || (dist_sq <= activation_dist_sq && (f.alert || physics::line_of_sight(&self.maze, block_size, f.pos, player.pos)));
+            if in_range && !f.alert {
+                self.stats.spotted += 1;
+                self.sounds.push(SoundEvent::Alert(f.pos));
+            }
+            f.alert = in_range;
+            if !in_range || dist_sq <= stop_dist_sq { continue; }
+            let dist = dist_sq.sqrt();
+            if dist < 1.0 { continue; }
+            let nx = dx / dist;
+            let ny = dy / dist;
+            let step = chase_speed * dt;
+            let enemy_radius = physics::radius(f.id, block_size);
+            let try_x = f.pos.x + nx * step;
+            if is_walkable_with_radius(try_x, f.pos.y, &self.maze, block_size, enemy_radius) { f.pos.x = try_x; }
+            let try_y = f.pos.y + ny * step;
+            if is_walkable_with_radius(f.pos.x, try_y, &self.maze, block_size, enemy_radius) { f.pos.y = try_y; }
+        }
+
+        // a chaser touching the player attacks, then waits for its cooldown
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
+        let player_radius = physics::player_radius(block_size);
+        let mut damage = 0.0;
+        for e in &mut self.enemies {
+            e.cooldown = (e.cooldown - dt).max(0.0);
+            if e.id != 'f' || e.cooldown > 0.0 || self.invulnerable > 0.0 { continue; }
+            if physics::contact(&self.maze, block_size, player.pos, player_radius, e.pos, physics::radius(e.id, block_size)) {
+                e.cooldown = ATTACK_COOLDOWN;
+                damage += CONTACT_DAMAGE;
+            }
+        }
+        if damage > 0.0 && self.hurt(damage) {
+            self.sounds.push(SoundEvent::Lose);
+            return GameState::GameOver;
+        }
+
+        //checks if the player has picked up the key (the player may have just respawned)
+        let player = self.player;
+        if !self.has_key {
+            let maze = &self.maze;
+            let mut picked = false;
+            self.enemies.retain(|e| {
+                if e.id == 'k' && physics::contact(maze, block_size, player.pos, player_radius, e.pos, physics::radius('k', block_size)) {
+                    picked = true;
+                    return false;
+                }
+                true
+            });
+            if picked {
+                self.has_key = true;
+                self.stats.items += 1;
+                self.sounds.push(SoundEvent::Pickup);
+            }
+        }
+
+        // victory if the player has the key and is near the iglo, without it the door stays shut
+        let at_door = self.near_goal();
+        if at_door && self.has_key {
+            self.sounds.push(SoundEvent::Win);
+            return GameState::Win;
+        }
+        if at_door && !self.at_door { self.sounds.push(SoundEvent::Door); }
+        self.at_door = at_door;
+
+        GameState::Playing
+    }
+
+    // takes `damage`, returns true when the last life is gone
+    fn hurt(&mut self, damage: f32) -> bool {
+        self.health -= damage;
+        self.invulnerable = INVULNERABILITY;
+        self.sounds.push(SoundEvent::Hurt);
+        if self.health > 0.0 { return false; }
+        if self.lives <= 1 {
+            self.lives = 0;
+            self.health = 0.0;
+            return true;
+        }
+        // next life: back to the start with full health, the enemies stay where they are
+        self.lives -= 1;
+        self.health = MAX_HEALTH;
+        self.invulnerable = RESPAWN_INVULNERABILITY;
+        self.player = self.spawn;
+        self.prev_player = self.spawn;
+        false
+    }
+
+    fn near_goal(&self) -> bool {
+        let block_size = self.block_size;
+        let maze = &self.maze;
+        let pos = self.player.pos;
+        let player_cell_x = (pos.x / block_size as f32) as isize;
+        let player_cell_y = (pos.y / block_size as f32) as isize;
+        let search_radius_cells: isize = 2;
+        let player_radius = physics::player_radius(block_size);
+        let goal_radius = physics::radius('g', block_size);
+        for cy in (player_cell_y - search_radius_cells)..=(player_cell_y + search_radius_cells) {
+            if cy < 0 || cy as usize >= maze.len() { continue; }
+            for cx in (player_cell_x - search_radius_cells)..=(player_cell_x + search_radius_cells) {
+                if cx < 0 || cx as usize >= maze[0].len() { continue; }
+                if maze[cy as usize][cx as usize] == 'g' {
+                    let center = Vector2::new((cx as f32 + 0.5) * block_size as f32, (cy as f32 + 0.5) * block_size as f32);
+                    if physics::contact(maze, block_size, pos, player_radius, center, goal_radius) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+// Check if a position is walkable in the maze
+pub fn is_walkable_world(x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
+    let cx = (x / block_size as f32) as isize;
+    let cy = (y / block_size as f32) as isize;
+    if cy < 0 || cx < 0 { return false; }
+    if cy as usize >= maze.len() || cx as usize >= maze[0].len() { return false; }
+    maze[cy as usize][cx as usize] == ' '
+}
+
+pub fn is_walkable_with_radius(x: f32, y: f32, maze: &Maze, block_size: usize, radius: f32) -> bool {
+    let points = [
+        (x, y),
+        (x + radius, y),
+        (x - radius, y),
+        (x, y + radius),
+        (x, y - radius),
+    ];
+    for (px, py) in points {
+        if px < 0.0 || py < 0.0 { return false; }
+        let gx = (px as usize) / block_size;
+        let gy = (py as usize) / block_size;
+        if gy >= maze.len() || gx >= maze[0].len() { return false; }
+        if maze[gy][gx] != ' ' { return false; }
+    }
+    true
+}